@@ -1,496 +1,981 @@
-use std::fmt;
+use std::{error::Error, fmt, sync::Arc};
 
 use crate::{
+    piece::{Kind, Piece},
     player::Player,
-    r#move::{Move, Position},
+    r#move::{LegalMove, Move, Position},
+    reason::Reason,
+    rules::{self, Crowning, Rules, DIAGONALS},
 };
 
-#[derive(Debug)]
+/// The playable squares are stored as bits, numbered row by row from
+/// `(0, 0)`, so on an 8x8 board square `(x, y)` is bit `4 * y + x / 2`. How
+/// big the board is and how the pieces move is up to the `RuleSet` the board
+/// is played with.
+#[derive(Debug, Clone)]
 pub struct Board {
-    pub coords: Vec<Vec<Option<Player>>>,
+    layout: Arc<Layout>,
+    red: u128,
+    white: u128,
+    kings: u128,
+    /// How many king moves without a capture red and white have made in a
+    /// row, for rules that limit them
+    king_moves: [u32; 2],
 }
 
-const INITIAL_WHITE_POSITIONS: [(i32, i32); 12] = [
+/// Every direction a piece can move in under any rules besides the
+/// diagonals, the orthogonal ones two squares long as well for boards that
+/// only use the dark squares
+const ORTHOGONALS: [(i32, i32); 8] = [
     (1, 0),
-    (3, 0),
-    (5, 0),
-    (7, 0),
+    (-1, 0),
     (0, 1),
-    (2, 1),
-    (4, 1),
-    (6, 1),
-    (1, 2),
-    (3, 2),
-    (5, 2),
-    (7, 2),
+    (0, -1),
+    (2, 0),
+    (-2, 0),
+    (0, 2),
+    (0, -2),
 ];
 
-const INITIAL_RED_POSITIONS: [(i32, i32); 12] = [
-    (0, 5),
-    (2, 5),
-    (4, 5),
-    (6, 5),
-    (1, 6),
-    (3, 6),
-    (5, 6),
-    (7, 6),
-    (0, 7),
-    (2, 7),
-    (4, 7),
-    (6, 7),
-];
+/// Squares whose bits move by the same amount, and by how much
+type ShiftGroup = (u128, i32);
+
+/// Where the bit of each square is, worked out once for the rules a board
+/// is played with
+#[derive(Debug)]
+struct Layout {
+    rules: Rules,
+    width: i32,
+    height: i32,
+    /// The bit index of each square row by row, `None` when it isn't playable
+    indices: Vec<Option<u32>>,
+    /// The square of each bit index
+    positions: Vec<Position>,
+    /// For each direction, groups of squares whose bits move by the same
+    /// amount when stepping that way
+    shifts: Vec<((i32, i32), Vec<ShiftGroup>)>,
+}
+
+impl Layout {
+    fn new(rules: Rules) -> Self {
+        let (width, height) = rules.size();
+
+        let positions: Vec<Position> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Position { x, y }))
+            .filter(|position| rules.is_playable(*position))
+            .collect();
+
+        assert!(
+            positions.len() <= 128,
+            "a board can't have more than 128 playable squares"
+        );
+
+        let mut indices = vec![None; (width * height) as usize];
+
+        for (index, position) in positions.iter().enumerate() {
+            indices[(position.y * width + position.x) as usize] = Some(index as u32);
+        }
+
+        let mut layout = Self {
+            rules,
+            width,
+            height,
+            indices,
+            positions,
+            shifts: vec![],
+        };
+
+        layout.shifts = DIAGONALS
+            .iter()
+            .chain(&ORTHOGONALS)
+            .map(|&(dx, dy)| {
+                let mut groups: Vec<ShiftGroup> = vec![];
+
+                for (index, position) in layout.positions.iter().enumerate() {
+                    let target = match layout.index(position.x + dx, position.y + dy) {
+                        Some(target) => target,
+                        None => continue,
+                    };
+
+                    let shift = target as i32 - index as i32;
+
+                    match groups.iter_mut().find(|(_, other)| *other == shift) {
+                        Some((mask, _)) => *mask |= 1 << index,
+                        None => groups.push((1 << index, shift)),
+                    }
+                }
+
+                ((dx, dy), groups)
+            })
+            .collect();
+
+        layout
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<u32> {
+        if !(0..self.width).contains(&x) || !(0..self.height).contains(&y) {
+            return None;
+        }
+
+        self.indices[(y * self.width + x) as usize]
+    }
+}
+
+/// A square that can't hold a piece, either off the board or light
+#[derive(Debug, PartialEq)]
+pub struct InvalidSquare(pub Position);
+
+impl fmt::Display for InvalidSquare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is not a dark square on the board", self.0)
+    }
+}
+
+impl Error for InvalidSquare {}
+
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.rules().name() == other.rules().name()
+            && self.red == other.red
+            && self.white == other.white
+            && self.kings == other.kings
+            && self.king_moves == other.king_moves
+    }
+}
+
+impl Eq for Board {}
 
 impl Board {
+    /// The starting position of American checkers
     pub fn new() -> Self {
+        Self::with_rules(rules::american())
+    }
+
+    /// The starting position of the given rules
+    pub fn with_rules(rules: Rules) -> Self {
+        let pieces = rules.starting_pieces();
+
+        Self::from_pieces_with_rules(rules, pieces).expect("the starting pieces are on the board")
+    }
+
+    /// An American checkers board without any pieces on it
+    pub fn empty() -> Self {
+        Self::empty_with_rules(rules::american())
+    }
+
+    /// A board for the given rules without any pieces on it
+    pub fn empty_with_rules(rules: Rules) -> Self {
         Self {
-            coords: (0..8).fold(vec![], |mut rows, x| {
-                let row = (0..8).fold(vec![], |mut row, y| {
-                    let coord = if INITIAL_WHITE_POSITIONS.contains(&(x, y)) {
-                        Some(Player::White)
-                    } else if INITIAL_RED_POSITIONS.contains(&(x, y)) {
-                        Some(Player::Red)
-                    } else {
-                        None
-                    };
+            layout: Arc::new(Layout::new(rules)),
+            red: 0,
+            white: 0,
+            kings: 0,
+            king_moves: [0; 2],
+        }
+    }
 
-                    row.push(coord);
+    /// Builds an American checkers board holding exactly the given pieces
+    pub fn from_pieces(
+        pieces: impl IntoIterator<Item = (Position, Piece)>,
+    ) -> Result<Self, InvalidSquare> {
+        Self::from_pieces_with_rules(rules::american(), pieces)
+    }
 
-                    row
-                });
+    /// Builds a board for the given rules holding exactly the given pieces
+    pub fn from_pieces_with_rules(
+        rules: Rules,
+        pieces: impl IntoIterator<Item = (Position, Piece)>,
+    ) -> Result<Self, InvalidSquare> {
+        let mut board = Self::empty_with_rules(rules);
 
-                rows.push(row);
+        for (position, piece) in pieces {
+            if board.bit(position.x, position.y).is_none() {
+                return Err(InvalidSquare(position));
+            }
 
-                rows
-            }),
+            board.set(position.x as usize, position.y as usize, Some(piece));
         }
+
+        Ok(board)
+    }
+
+    /// The rules this board is played with
+    pub fn rules(&self) -> &Rules {
+        &self.layout.rules
     }
 
-    pub fn make_move(&mut self, current_player: &Player, mov: &Move) -> bool {
+    /// The width and height of the board in squares
+    pub fn size(&self) -> (i32, i32) {
+        (self.layout.width, self.layout.height)
+    }
+
+    /// Makes a single step or jump, returning the square of the piece it
+    /// captured if any
+    pub fn make_move(
+        &mut self,
+        current_player: &Player,
+        mov: &Move,
+    ) -> Result<Option<Position>, Reason> {
         let Move {
             initial,
             destination,
             ..
         } = mov;
 
-        if !mov.is_valid(current_player) {
-            return false;
+        let rules = self.rules().clone();
+
+        if !mov.is_on_board(&*rules) {
+            return Err(Reason::OutOfBounds);
         }
 
-        let opt_player = self.get(initial.x as usize, initial.y as usize);
+        let piece = self
+            .get(initial.x as usize, initial.y as usize)
+            .ok_or(Reason::EmptySquare)?;
 
-        if opt_player.is_none() {
-            return false;
+        if current_player != &piece.player {
+            return Err(Reason::OpponentsPiece);
         }
 
-        let player = opt_player.unwrap();
+        if !mov.is_valid(&*rules, &piece) {
+            // The same move would be fine for a king, either because it goes
+            // backwards or because it is a flying king's long move
+            if mov.is_valid(&*rules, &piece.crowned()) {
+                let x_diff = destination.x - initial.x;
+                let y_diff = destination.y - initial.y;
 
-        if current_player != player {
-            return false;
-        }
+                let backwards = y_diff * piece.player.forward() < 0;
 
-        let opt_dest_player = self.get(destination.x as usize, destination.y as usize);
+                if backwards && x_diff.abs().max(y_diff.abs()) <= 2 {
+                    return Err(Reason::Backwards);
+                }
+
+                return Err(Reason::TooFar);
+            }
+
+            let orthogonal = rules
+                .move_directions(&piece.crowned())
+                .iter()
+                .all(|(dx, dy)| *dx == 0 || *dy == 0);
 
-        if opt_dest_player.is_some() {
-            return false;
+            if orthogonal {
+                return Err(Reason::NotOrthogonal);
+            }
+
+            return Err(Reason::NotDiagonal);
         }
 
-        if !mov.is_jump(current_player) && self.has_legal_jumps(current_player) {
-            return false;
+        let opt_dest_piece = self.get(destination.x as usize, destination.y as usize);
+
+        if opt_dest_piece.is_some() {
+            return Err(Reason::Occupied);
         }
 
-        if let Some(pos) = mov.jumped_position(current_player) {
-            let Position { x, y } = pos;
+        let jumped = self.between(mov);
 
-            let opt_jumped_player = self.get(x as usize, y as usize);
+        let captured = match jumped.as_slice() {
+            [] if mov.is_step(&*rules, &piece) => {
+                if rules.compulsory_capture() {
+                    if let Some(pos) = self.jumping_piece(current_player) {
+                        return Err(Reason::CompulsoryJump(pos));
+                    }
+                }
+
+                if piece.is_king() && self.kings_held_back(current_player) {
+                    return Err(Reason::KingMoveLimit);
+                }
 
-            if opt_jumped_player.is_none() {
-                return false;
+                None
             }
+            [] => return Err(Reason::NothingToJump),
+            [(pos, jumped_piece)] => {
+                if current_player == &jumped_piece.player {
+                    return Err(Reason::JumpsOwnPiece);
+                }
 
-            let jumped_player = opt_jumped_player.unwrap();
+                if !rules.can_capture(&piece, jumped_piece) {
+                    return Err(Reason::CantCapture);
+                }
 
-            if current_player == jumped_player {
-                return false;
+                Some(*pos)
             }
+            _ => return Err(Reason::JumpsSeveralPieces),
+        };
 
+        if let Some(Position { x, y }) = captured {
             self.set(x as usize, y as usize, None);
         }
 
+        // A man that may still have to carry on capturing as a man isn't
+        // crowned until the whole capture is over
+        let crown = rules.promotes(&piece, *destination)
+            && !(captured.is_some() && rules.crowning() == Crowning::ContinuesAsMan);
+
+        let piece = if crown { piece.crowned() } else { piece };
+
         self.set(initial.x as usize, initial.y as usize, None);
-        self.set(
-            destination.x as usize,
-            destination.y as usize,
-            Some(*current_player),
-        );
+        self.set(destination.x as usize, destination.y as usize, Some(piece));
 
-        return true;
+        Ok(captured)
+    }
+
+    /// The pieces on the squares strictly between the two ends of `mov`
+    fn between(&self, mov: &Move) -> Vec<(Position, Piece)> {
+        let ((dx, dy), distance) = match mov.direction() {
+            Some(direction) => direction,
+            None => return vec![],
+        };
+
+        (1..distance)
+            .map(|n| Position {
+                x: mov.initial.x + n * dx,
+                y: mov.initial.y + n * dy,
+            })
+            .filter_map(|position| {
+                self.square(position.x, position.y)
+                    .flatten()
+                    .map(|piece| (position, piece))
+            })
+            .collect()
+    }
+
+    fn set(&mut self, x: usize, y: usize, value: Option<Piece>) {
+        let bit = match self.bit(x as i32, y as i32) {
+            Some(bit) => bit,
+            None => return,
+        };
+
+        self.red &= !bit;
+        self.white &= !bit;
+        self.kings &= !bit;
+
+        if let Some(piece) = value {
+            match piece.player {
+                Player::Red => self.red |= bit,
+                Player::White => self.white |= bit,
+            }
+
+            if piece.is_king() {
+                self.kings |= bit;
+            }
+        }
+    }
+
+    /// The piece on `(x, y)`, `None` for empty, light or off board squares
+    pub fn get(&self, x: usize, y: usize) -> Option<Piece> {
+        if x > i32::MAX as usize || y > i32::MAX as usize {
+            return None;
+        }
+
+        self.piece_at(self.bit(x as i32, y as i32)?)
+    }
+
+    /// Looks up a square without panicking, `None` means the
+    /// coordinates are off the board or on a light square
+    fn square(&self, x: i32, y: i32) -> Option<Option<Piece>> {
+        self.bit(x, y).map(|bit| self.piece_at(bit))
+    }
+
+    fn piece_at(&self, bit: u128) -> Option<Piece> {
+        let player = if self.red & bit != 0 {
+            Player::Red
+        } else if self.white & bit != 0 {
+            Player::White
+        } else {
+            return None;
+        };
+
+        if self.kings & bit != 0 {
+            Some(Piece::king(player))
+        } else {
+            Some(Piece::man(player))
+        }
+    }
+
+    fn own(&self, player: &Player) -> u128 {
+        match player {
+            Player::Red => self.red,
+            Player::White => self.white,
+        }
     }
 
-    fn set(&mut self, x: usize, y: usize, value: Option<Player>) {
-        self.coords[x][y] = value;
+    fn vacant(&self) -> u128 {
+        self.all() & !(self.red | self.white)
     }
 
-    pub fn get(&mut self, x: usize, y: usize) -> Option<&Player> {
-        self.coords[x][y].as_ref()
+    /// Every playable square
+    fn all(&self) -> u128 {
+        u128::MAX >> (128 - self.layout.positions.len())
+    }
+
+    /// The men and the kings of `player`, each with the squares they are on
+    fn forces(&self, player: &Player) -> [(Piece, u128); 2] {
+        let own = self.own(player);
+
+        [
+            (Piece::man(*player), own & !self.kings),
+            (Piece::king(*player), own & self.kings),
+        ]
+    }
+
+    /// The squares the pieces in `movers`, all like `piece`, land on by
+    /// jumping in `direction`
+    fn jump_landings(&self, movers: u128, piece: &Piece, direction: (i32, i32)) -> u128 {
+        let rules = self.rules();
+        let opponent = piece.player.opponent();
+        let vacant = self.vacant();
+
+        // Only the kinds of piece the rules let `piece` capture
+        let capturable = [
+            (Piece::man(opponent), !self.kings),
+            (Piece::king(opponent), self.kings),
+        ]
+        .iter()
+        .filter(|(target, _)| rules.can_capture(piece, target))
+        .fold(0, |mask, (_, kind)| mask | kind)
+            & self.own(&opponent);
+
+        let mut from = movers;
+
+        // Flying kings can jump from anywhere along an empty line
+        if rules.is_flying(piece) {
+            let mut ray = self.step(movers, direction) & vacant;
+
+            while ray != 0 {
+                from |= ray;
+                ray = self.step(ray, direction) & vacant;
+            }
+        }
+
+        self.step(self.step(from, direction) & capturable, direction) & vacant
     }
 
     pub fn red_score(&self) -> usize {
-        self.coords
-            .iter()
-            .flatten()
-            .filter(|coord| coord.is_some())
-            .fold(0, |acc, opt_coord| {
-                if opt_coord.unwrap().is_red() {
-                    acc + 1
-                } else {
-                    acc
-                }
-            })
+        self.count(&Player::Red)
     }
 
     pub fn white_score(&self) -> usize {
-        self.coords
+        self.count(&Player::White)
+    }
+
+    /// How many pieces `player` has left, men and kings
+    pub fn count(&self, player: &Player) -> usize {
+        self.own(player).count_ones() as usize
+    }
+
+    /// How many of the pieces `player` has left are kings
+    pub fn king_count(&self, player: &Player) -> usize {
+        (self.own(player) & self.kings).count_ones() as usize
+    }
+
+    /// Every occupied square along with its piece, row by row
+    pub fn occupied(&self) -> impl Iterator<Item = (Position, Piece)> + '_ {
+        squares(self.red | self.white).filter_map(move |bit| {
+            let position = self.position(bit);
+
+            self.piece_at(bit).map(|piece| (position, piece))
+        })
+    }
+
+    pub fn has_legal_jumps(&self, current_player: &Player) -> bool {
+        self.forces(current_player)
             .iter()
-            .flatten()
-            .filter(|coord| coord.is_some())
-            .fold(0, |acc, opt_coord| {
-                if opt_coord.unwrap().is_white() {
-                    acc + 1
-                } else {
-                    acc
-                }
+            .filter(|(_, movers)| *movers != 0)
+            .any(|(piece, movers)| {
+                self.rules()
+                    .capture_directions(piece)
+                    .into_iter()
+                    .any(|direction| self.jump_landings(*movers, piece, direction) != 0)
             })
     }
 
-    pub fn is_jumping_possible(&self, player: &Player, mov: &Move) -> bool {
-        let Position { x, y } = mov.initial;
+    pub fn has_legal_moves(&self, current_player: &Player) -> bool {
+        let held_back = self.kings_held_back(current_player);
+
+        self.has_legal_jumps(current_player)
+            || self.forces(current_player).iter().any(|(piece, movers)| {
+                !(piece.is_king() && held_back)
+                    && self
+                        .rules()
+                        .move_directions(piece)
+                        .into_iter()
+                        .any(|direction| self.step(*movers, direction) & self.vacant() != 0)
+            })
+    }
 
-        match player {
-            Player::Red => {
-                // check right
-                let right = match self.coords.get((x + 1) as usize) {
-                    // Out of bounds but that's ok we just keep going
-                    None => false,
-                    Some(row) => match row.get((y - 1) as usize) {
-                        // Out of bounds but that's ok we just keep going
-                        None => false,
-                        Some(spot) => match spot {
-                            // Empty Spot, the original x, y could move here
-                            None => false,
-                            Some(other_player) => {
-                                // We might be able to jump in this case
-                                if other_player != player {
-                                    match self.coords.get((x + 2) as usize) {
-                                        // Out of bounds but that's ok we just keep going
-                                        None => false,
-                                        Some(row) => match row.get((y - 2) as usize) {
-                                            // Out of bounds but that's ok we just keep going
-                                            None => false,
-                                            Some(spot) => match spot {
-                                                None => true,
-                                                // Occupied
-                                                Some(_) => false,
-                                            },
-                                        },
-                                    }
-                                } else {
-                                    false
-                                }
-                            }
-                        },
-                    },
-                };
+    /// Whether `player` has used up the king moves without a capture the
+    /// rules allow in a row and has to move a man instead
+    pub fn kings_held_back(&self, player: &Player) -> bool {
+        let limit = match self.rules().king_move_limit() {
+            Some(limit) => limit,
+            None => return false,
+        };
 
-                // check left
-                let left = match self.coords.get((x - 1) as usize) {
-                    // Out of bounds but that's ok we just keep going
-                    None => false,
-                    Some(row) => match row.get((y - 1) as usize) {
-                        // Out of bounds but that's ok we just keep going
-                        None => false,
-                        Some(spot) => match spot {
-                            // Empty Spot, the original x, y could move here
-                            None => false,
-                            Some(other_player) => {
-                                // We might be able to jump in this case
-                                if other_player != player {
-                                    match self.coords.get((x - 2) as usize) {
-                                        // Out of bounds but that's ok we just keep going
-                                        None => false,
-                                        Some(row) => match row.get((y - 2) as usize) {
-                                            // Out of bounds but that's ok we just keep going
-                                            None => false,
-                                            Some(spot) => match spot {
-                                                None => true,
-                                                // Occupied
-                                                Some(_) => false,
-                                            },
-                                        },
-                                    }
-                                } else {
-                                    false
-                                }
-                            }
-                        },
-                    },
-                };
+        self.king_moves[king_moves_index(player)] >= limit && self.own(player) & !self.kings != 0
+    }
+
+    /// The first piece of `player` that is able to jump, if any
+    pub fn jumping_piece(&self, player: &Player) -> Option<Position> {
+        self.pieces(player)
+            .find(|(x, y, piece)| self.can_jump_from(*x, *y, piece))
+            .map(|(x, y, _)| Position { x, y })
+    }
+
+    /// Every legal move `player` can make, multi-jumps are listed as a single
+    /// move and when captures are compulsory and any is available only
+    /// captures are returned, narrowed down by `RuleSet::choose_captures`
+    pub fn legal_moves(&self, player: &Player) -> Vec<LegalMove> {
+        let captures: Vec<LegalMove> = self
+            .pieces(player)
+            .flat_map(|(x, y, piece)| self.jumps_from(Position { x, y }, &piece))
+            .collect();
+
+        let mut moves = if captures.is_empty() {
+            captures
+        } else {
+            self.rules().choose_captures(self, captures)
+        };
+
+        if !moves.is_empty() && self.rules().compulsory_capture() {
+            return moves;
+        }
 
-                left || right
+        let held_back = self.kings_held_back(player);
+
+        for (x, y, piece) in self.pieces(player) {
+            if piece.is_king() && held_back {
+                continue;
             }
-            Player::White => {
-                // check right
-                let right = match self.coords.get((x + 1) as usize) {
-                    None => false,
-                    Some(row) => match row.get((y + 1) as usize) {
-                        None => false,
-                        Some(spot) => match spot {
-                            None => false,
-                            Some(other_player) => {
-                                // We might be able to jump in this case
-                                if other_player != player {
-                                    match self.coords.get((x + 2) as usize) {
-                                        None => false,
-                                        Some(row) => match row.get((y + 2) as usize) {
-                                            None => false,
-                                            Some(spot) => match spot {
-                                                None => true,
-                                                // Occupied
-                                                Some(_) => false,
-                                            },
-                                        },
-                                    }
-                                } else {
-                                    false
-                                }
-                            }
-                        },
-                    },
-                };
 
-                // check left
-                let left = match self.coords.get((x - 1) as usize) {
-                    None => false,
-                    Some(row) => match row.get((y + 1) as usize) {
-                        None => false,
-                        Some(spot) => match spot {
-                            None => false,
-                            Some(other_player) => {
-                                // We might be able to jump in this case
-                                if other_player != player {
-                                    match self.coords.get((x - 2) as usize) {
-                                        None => false,
-                                        Some(row) => match row.get((y + 2) as usize) {
-                                            None => false,
-                                            Some(spot) => match spot {
-                                                None => true,
-                                                // Occupied
-                                                Some(_) => false,
-                                            },
-                                        },
-                                    }
-                                } else {
-                                    false
-                                }
-                            }
-                        },
-                    },
-                };
+            let range = if self.rules().is_flying(&piece) {
+                i32::MAX
+            } else {
+                1
+            };
+
+            for (dx, dy) in self.rules().move_directions(&piece) {
+                for n in 1..=range {
+                    let destination = Position {
+                        x: x + n * dx,
+                        y: y + n * dy,
+                    };
+
+                    if !matches!(self.square(destination.x, destination.y), Some(None)) {
+                        break;
+                    }
 
-                left || right
+                    moves.push(LegalMove {
+                        path: vec![Position { x, y }, destination],
+                        captured: vec![],
+                    });
+                }
             }
         }
+
+        moves
     }
 
-    pub fn has_legal_jumps(&self, current_player: &Player) -> bool {
-        for y in 0..8 {
-            for x in 0..8 {
-                let x: i32 = x;
-                let y: i32 = y;
+    /// Plays a move produced by `legal_moves`, removing every captured
+    /// piece and crowning the piece if the rules say so
+    pub fn apply(&mut self, mov: &LegalMove) {
+        let initial = mov.initial();
+        let destination = mov.destination();
 
-                let (y_offset_one, y_offset_two) = if current_player.is_red() {
-                    (y - 1, y - 2)
-                } else {
-                    (y + 1, y + 2)
-                };
+        let piece = self
+            .get(initial.x as usize, initial.y as usize)
+            .expect("a legal move starts on a piece");
+
+        self.set(initial.x as usize, initial.y as usize, None);
+
+        let king_moves = &mut self.king_moves[king_moves_index(&piece.player)];
+
+        *king_moves = if piece.is_king() && !mov.is_capture() {
+            *king_moves + 1
+        } else {
+            0
+        };
+
+        for Position { x, y } in &mov.captured {
+            self.set(*x as usize, *y as usize, None);
+        }
+
+        let rules = self.rules();
+
+        // Depending on the rules a man crowned part way through a capture
+        // stays a king
+        let crown = rules.promotes(&piece, destination)
+            || (rules.crowning() == Crowning::ContinuesAsKing
+                && mov.is_capture()
+                && mov
+                    .path
+                    .iter()
+                    .any(|position| rules.promotes(&piece, *position)));
+
+        let piece = if crown { piece.crowned() } else { piece };
+
+        self.set(destination.x as usize, destination.y as usize, Some(piece));
+    }
+
+    /// Counts the positions reachable in exactly `depth` moves with
+    /// `player` moving first
+    pub fn perft(&self, player: &Player, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.legal_moves(player);
+
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        moves
+            .iter()
+            .map(|mov| self.perft_after(player, mov, depth))
+            .sum()
+    }
+
+    /// Same as `perft` but broken down by the first move
+    pub fn divide(&self, player: &Player, depth: usize) -> Vec<(LegalMove, u64)> {
+        if depth == 0 {
+            return vec![];
+        }
+
+        self.legal_moves(player)
+            .into_iter()
+            .map(|mov| {
+                let nodes = self.perft_after(player, &mov, depth);
+
+                (mov, nodes)
+            })
+            .collect()
+    }
+
+    fn perft_after(&self, player: &Player, mov: &LegalMove, depth: usize) -> u64 {
+        let mut board = self.clone();
+
+        board.apply(mov);
+
+        board.perft(&player.opponent(), depth - 1)
+    }
+
+    fn jumps_from(&self, initial: Position, piece: &Piece) -> Vec<LegalMove> {
+        let mut moves = vec![];
+
+        self.extend_jumps(
+            piece,
+            &LegalMove {
+                path: vec![initial],
+                captured: vec![],
+            },
+            &mut moves,
+        );
+
+        moves
+    }
+
+    /// Follows every jump available at the end of `sequence`, pushing
+    /// each sequence that can't be extended any further onto `moves`.
+    /// Captured pieces stay on the board until the end of the move, so they
+    /// can't be jumped twice and block the way.
+    fn extend_jumps(&self, piece: &Piece, sequence: &LegalMove, moves: &mut Vec<LegalMove>) {
+        let rules = self.rules();
+        let flying = rules.is_flying(piece);
+
+        let mut extended = false;
+
+        // The direction of the last jump, which can't be reversed when the
+        // pieces jumped so far are already off the board
+        let previous = match sequence.path.as_slice() {
+            [.., from, to] if rules.removes_captured_immediately() => {
+                Some(((to.x - from.x).signum(), (to.y - from.y).signum()))
+            }
+            _ => None,
+        };
+
+        for (dx, dy) in rules.capture_directions(piece) {
+            if previous == Some((-dx, -dy)) {
+                continue;
+            }
+
+            let next = |position: Position| Position {
+                x: position.x + dx,
+                y: position.y + dy,
+            };
+
+            let mut jumped = next(sequence.destination());
+
+            while flying && self.is_free(jumped, sequence) {
+                jumped = next(jumped);
+            }
+
+            let over_opponent = match self.square(jumped.x, jumped.y) {
+                Some(Some(other_piece)) => {
+                    other_piece.player != piece.player
+                        && rules.can_capture(piece, &other_piece)
+                        && !sequence.captured.contains(&jumped)
+                }
+                _ => false,
+            };
+
+            if !over_opponent {
+                continue;
+            }
+
+            // What each landing square leads to and whether the capture
+            // carries on from there, a flying king has to land where it
+            // can keep capturing if there is such a square
+            let mut landings: Vec<(bool, Vec<LegalMove>)> = vec![];
 
-                match &self.coords[x as usize][y as usize] {
-                    None => (),
-                    Some(player) if player == current_player => {
-                        // check right
-                        match self.coords.get((x + 1) as usize) {
-                            // Out of bounds but that's ok we just keep going
-                            None => (),
-                            Some(row) => match row.get(y_offset_one as usize) {
-                                // Out of bounds but that's ok we just keep going
-                                None => (),
-                                Some(spot) => match spot {
-                                    None => (),
-                                    Some(other_player) => {
-                                        // We might be able to jump in this case
-                                        if other_player != player {
-                                            match self.coords.get((x + 2) as usize) {
-                                                // Out of bounds but that's ok we just keep going
-                                                None => (),
-                                                Some(row) => match row.get(y_offset_two as usize) {
-                                                    // Out of bounds but that's ok we just keep going
-                                                    None => (),
-                                                    Some(spot) => match spot {
-                                                        None => return true,
-                                                        // Occupied
-                                                        Some(_) => (),
-                                                    },
-                                                },
-                                            }
-                                        }
-                                    }
-                                },
-                            },
-                        };
-
-                        // check left
-                        match self.coords.get((x - 1) as usize) {
-                            // Out of bounds but that's ok we just keep going
-                            None => (),
-                            Some(row) => match row.get(y_offset_one as usize) {
-                                // Out of bounds but that's ok we just keep going
-                                None => (),
-                                Some(spot) => match spot {
-                                    // Empty Spot, the original x, y could move here
-                                    None => (),
-                                    Some(other_player) => {
-                                        // We might be able to jump in this case
-                                        if other_player != player {
-                                            match self.coords.get((x - 2) as usize) {
-                                                // Out of bounds but that's ok we just keep going
-                                                None => (),
-                                                Some(row) => match row.get(y_offset_two as usize) {
-                                                    // Out of bounds but that's ok we just keep going
-                                                    None => (),
-                                                    Some(spot) => match spot {
-                                                        None => return true,
-                                                        // Occupied
-                                                        Some(_) => (),
-                                                    },
-                                                },
-                                            }
-                                        }
-                                    }
-                                },
-                            },
-                        };
+            let mut landing = next(jumped);
+
+            while self.is_free(landing, sequence) {
+                extended = true;
+
+                let mut next_sequence = sequence.clone();
+
+                next_sequence.path.push(landing);
+                next_sequence.captured.push(jumped);
+
+                let mut continued = vec![];
+
+                if !rules.promotes(piece, landing) {
+                    self.extend_jumps(piece, &next_sequence, &mut continued);
+                } else {
+                    match rules.crowning() {
+                        Crowning::EndsCapture => continued.push(next_sequence.clone()),
+                        Crowning::ContinuesAsKing => {
+                            self.extend_jumps(&piece.crowned(), &next_sequence, &mut continued)
+                        }
+                        Crowning::ContinuesAsMan => {
+                            self.extend_jumps(piece, &next_sequence, &mut continued)
+                        }
                     }
-                    _ => (),
+                }
+
+                let carries_on = continued
+                    .iter()
+                    .any(|mov| mov.captured.len() > next_sequence.captured.len());
+
+                landings.push((carries_on, continued));
+
+                if !flying {
+                    break;
+                }
+
+                landing = next(landing);
+            }
+
+            let must_carry_on = landings.iter().any(|(carries_on, _)| *carries_on);
+
+            for (carries_on, continued) in landings {
+                if carries_on || !must_carry_on {
+                    moves.extend(continued);
                 }
             }
         }
 
-        false
+        if !extended && sequence.is_capture() {
+            moves.push(sequence.clone());
+        }
     }
 
-    pub fn has_legal_moves(&self, current_player: &Player) -> bool {
-        for y in 0..8 {
-            for x in 0..8 {
-                let x: i32 = x;
-                let y: i32 = y;
+    /// Whether the piece making `sequence` can pass over or land on
+    /// `position`, the square it started on is free once it has left and
+    /// so are the squares it captured on if they are emptied straight away
+    fn is_free(&self, position: Position, sequence: &LegalMove) -> bool {
+        position == sequence.initial()
+            || matches!(self.square(position.x, position.y), Some(None))
+            || (self.rules().removes_captured_immediately()
+                && sequence.captured.contains(&position))
+    }
 
-                let (y_offset_one, y_offset_two) = if current_player.is_red() {
-                    (y - 1, y - 2)
-                } else {
-                    (y + 1, y + 2)
-                };
+    /// Every piece belonging to `player` along with its coordinates
+    fn pieces(&self, player: &Player) -> impl Iterator<Item = (i32, i32, Piece)> + '_ {
+        squares(self.own(player)).filter_map(move |bit| {
+            let Position { x, y } = self.position(bit);
 
-                match &self.coords[x as usize][y as usize] {
-                    None => (),
-                    Some(player) if player == current_player => {
-                        // check right
-                        match self.coords.get((x + 1) as usize) {
-                            // Out of bounds but that's ok we just keep going
-                            None => (),
-                            Some(row) => match row.get(y_offset_one as usize) {
-                                // Out of bounds but that's ok we just keep going
-                                None => (),
-                                Some(spot) => match spot {
-                                    // Empty Spot, the original x, y could move here
-                                    None => return true,
-                                    Some(other_player) => {
-                                        // We might be able to jump in this case
-                                        if other_player != player {
-                                            match self.coords.get((x + 2) as usize) {
-                                                // Out of bounds but that's ok we just keep going
-                                                None => (),
-                                                Some(row) => match row.get(y_offset_two as usize) {
-                                                    // Out of bounds but that's ok we just keep going
-                                                    None => (),
-                                                    Some(spot) => match spot {
-                                                        None => return true,
-                                                        // Occupied
-                                                        Some(_) => (),
-                                                    },
-                                                },
-                                            }
-                                        }
-                                    }
-                                },
-                            },
-                        };
-
-                        // check left
-                        match self.coords.get((x - 1) as usize) {
-                            // Out of bounds but that's ok we just keep going
-                            None => (),
-                            Some(row) => match row.get(y_offset_one as usize) {
-                                // Out of bounds but that's ok we just keep going
-                                None => (),
-                                Some(spot) => match spot {
-                                    // Empty Spot, the original x, y could move here
-                                    None => return true,
-                                    Some(other_player) => {
-                                        // We might be able to jump in this case
-                                        if other_player != player {
-                                            match self.coords.get((x - 2) as usize) {
-                                                // Out of bounds but that's ok we just keep going
-                                                None => (),
-                                                Some(row) => match row.get(y_offset_two as usize) {
-                                                    // Out of bounds but that's ok we just keep going
-                                                    None => (),
-                                                    Some(spot) => match spot {
-                                                        None => return true,
-                                                        // Occupied
-                                                        Some(_) => (),
-                                                    },
-                                                },
-                                            }
-                                        }
-                                    }
-                                },
-                            },
-                        };
-                    }
-                    _ => (),
+            self.piece_at(bit).map(|piece| (x, y, piece))
+        })
+    }
+
+    fn can_jump_from(&self, x: i32, y: i32, piece: &Piece) -> bool {
+        let movers = match self.bit(x, y) {
+            Some(bit) => bit,
+            None => return false,
+        };
+
+        self.rules()
+            .capture_directions(piece)
+            .into_iter()
+            .any(|direction| self.jump_landings(movers, piece, direction) != 0)
+    }
+
+    /// The bit for square `(x, y)`, or `None` when it is off the board or
+    /// can't hold a piece
+    fn bit(&self, x: i32, y: i32) -> Option<u128> {
+        self.layout.index(x, y).map(|index| 1 << index)
+    }
+
+    /// The square of a single bit
+    fn position(&self, bit: u128) -> Position {
+        self.layout.positions[bit.trailing_zeros() as usize]
+    }
+
+    /// Moves every square in `bits` one step in `direction`, dropping the
+    /// squares that would leave the board
+    fn step(&self, bits: u128, direction: (i32, i32)) -> u128 {
+        let groups = self
+            .layout
+            .shifts
+            .iter()
+            .find(|(other, _)| *other == direction)
+            .map(|(_, groups)| groups.as_slice())
+            .unwrap_or_default();
+
+        groups.iter().fold(0, |moved, (mask, shift)| {
+            let bits = bits & mask;
+
+            moved
+                | if *shift >= 0 {
+                    bits << shift
+                } else {
+                    bits >> -shift
                 }
-            }
+        })
+    }
+}
+
+/// Each set bit of `mask` on its own, lowest first
+fn squares(mut mask: u128) -> impl Iterator<Item = u128> {
+    std::iter::from_fn(move || {
+        if mask == 0 {
+            return None;
         }
 
-        false
+        let bit = mask & mask.wrapping_neg();
+
+        mask &= !bit;
+
+        Some(bit)
+    })
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where a player's count is kept in `Board::king_moves`
+fn king_moves_index(player: &Player) -> usize {
+    match player {
+        Player::Red => 0,
+        Player::White => 1,
     }
 }
 
 impl fmt::Display for Board {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for y in 0..8 {
-            for x in 0..8 {
-                match &self.coords[x][y] {
+        let (width, height) = self.size();
+
+        // Ranks are right aligned so that the columns line up past rank 9
+        let margin = height.to_string().len();
+
+        // Files are lettered from high `x` to low `x`, see `Position::algebraic`
+        let files = (0..width)
+            .map(|x| format!(" {}", (b'a' + (width - 1 - x) as u8) as char))
+            .collect::<String>();
+
+        writeln!(f, "{:margin$}{}", "", files, margin = margin)?;
+
+        for y in 0..height {
+            write!(f, "{:>margin$}", y + 1, margin = margin)?;
+
+            for x in 0..width {
+                match self.square(x, y).flatten() {
                     None => write!(f, " _")?,
-                    Some(player) => match player {
-                        Player::Red => write!(f, " x")?,
-                        Player::White => write!(f, " o")?,
+                    Some(piece) => match (piece.player, piece.kind) {
+                        (Player::Red, Kind::Man) => write!(f, " x")?,
+                        (Player::Red, Kind::King) => write!(f, " X")?,
+                        (Player::White, Kind::Man) => write!(f, " o")?,
+                        (Player::White, Kind::King) => write!(f, " O")?,
                     },
                 };
             }
 
-            write!(f, "\n")?;
+            writeln!(f, " {}", y + 1)?;
         }
 
-        Ok(())
+        writeln!(f, "{:margin$}{}", "", files, margin = margin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opening_moves() {
+        let board = Board::new();
+
+        assert_eq!(board.legal_moves(&Player::White).len(), 7);
+        assert_eq!(board.legal_moves(&Player::Red).len(), 7);
+    }
+
+    #[test]
+    fn perft_from_start() {
+        let board = Board::new();
+
+        let nodes: Vec<u64> = (1..=6)
+            .map(|depth| board.perft(&Player::White, depth))
+            .collect();
+
+        assert_eq!(nodes, vec![7, 49, 302, 1469, 7361, 36768]);
+    }
+
+    #[test]
+    fn from_pieces() {
+        let board = Board::from_pieces(vec![
+            (Position { x: 1, y: 0 }, Piece::king(Player::White)),
+            (Position { x: 2, y: 5 }, Piece::man(Player::Red)),
+        ])
+        .unwrap();
+
+        assert_eq!(board.count(&Player::White), 1);
+        assert_eq!(board.king_count(&Player::White), 1);
+        assert_eq!(board.count(&Player::Red), 1);
+        assert_eq!(board.king_count(&Player::Red), 0);
+        assert_eq!(board.get(2, 5), Some(Piece::man(Player::Red)));
+        assert_eq!(board.occupied().count(), 2);
+
+        assert_eq!(
+            Board::from_pieces(vec![(Position { x: 0, y: 0 }, Piece::man(Player::Red))])
+                .unwrap_err(),
+            InvalidSquare(Position { x: 0, y: 0 })
+        );
+    }
+
+    #[test]
+    fn multi_jump_is_one_move() {
+        let mut board = Board::empty();
+
+        board.set(1, 0, Some(Piece::man(Player::White)));
+        board.set(2, 1, Some(Piece::man(Player::Red)));
+        board.set(4, 3, Some(Piece::man(Player::Red)));
+        board.set(6, 1, Some(Piece::man(Player::White)));
+
+        let moves = board.legal_moves(&Player::White);
+
+        assert_eq!(
+            moves,
+            vec![LegalMove {
+                path: vec![
+                    Position { x: 1, y: 0 },
+                    Position { x: 3, y: 2 },
+                    Position { x: 5, y: 4 },
+                ],
+                captured: vec![Position { x: 2, y: 1 }, Position { x: 4, y: 3 }],
+            }]
+        );
     }
 }
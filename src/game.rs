@@ -2,8 +2,11 @@ use std::fmt;
 
 use crate::{
     board::Board,
-    player::Player,
-    r#move::{Move, Position},
+    player::{Names, Player},
+    r#move::{LegalMove, Move, Notation, Position},
+    reason::Reason,
+    rules::Rules,
+    setup::Setup,
 };
 
 #[derive(Debug)]
@@ -12,33 +15,86 @@ pub struct Game<'a> {
     board: Board,
     moves: &'a Vec<Move>,
     debug: bool,
+    notation: Notation,
+    names: Names,
+    start: Setup,
+    played: Vec<LegalMove>,
+    in_turn: bool,
 }
 
 pub enum Validation<'a> {
-    Illegal(&'a Move),
+    Illegal(&'a Move, Reason),
     IncompleteGame,
     Tie,
     Winner(Player),
 }
 
-impl fmt::Display for Validation<'_> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl Validation<'_> {
+    /// The answer for the game, with an illegal move and the squares in its
+    /// reason written in the given notation and the winner called by `names`
+    pub fn notate(&self, notation: Notation, names: &Names) -> String {
         match self {
-            Self::Illegal(mov) => write!(f, "line {} illegal move: {}", mov.line, mov.src),
-            Self::IncompleteGame => write!(f, "incomplete game"),
-            Self::Tie => write!(f, "tie"),
-            Self::Winner(player) => write!(f, "{}", player),
+            Self::Illegal(mov, reason) => format!(
+                "line {} illegal move: {} ({})",
+                mov.line,
+                mov.notate(notation),
+                reason.notate(notation)
+            ),
+            Self::IncompleteGame => "incomplete game".to_string(),
+            Self::Tie => "tie".to_string(),
+            Self::Winner(player) => names.name(*player).to_string(),
         }
     }
 }
 
+impl fmt::Display for Validation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.notate(Notation::default(), &Names::default()))
+    }
+}
+
 impl<'a> Game<'a> {
     pub fn new(moves: &'a Vec<Move>) -> Self {
+        Self::with_setup(moves, Setup::default())
+    }
+
+    /// A game played with `rules` from their starting position
+    pub fn with_rules(moves: &'a Vec<Move>, rules: Rules) -> Self {
+        Self::with_setup(moves, Setup::new(rules))
+    }
+
+    /// A game played with `rules` from their starting position, with
+    /// `first_player` to move and the sides called by `names`
+    pub fn with_sides(
+        moves: &'a Vec<Move>,
+        rules: Rules,
+        first_player: Player,
+        names: Names,
+    ) -> Self {
+        let setup = Setup {
+            to_move: first_player,
+            ..Setup::new(rules)
+        };
+
+        let mut game = Self::with_setup(moves, setup);
+
+        game.set_names(names);
+
+        game
+    }
+
+    /// A game that starts from `setup` instead of the usual starting position
+    pub fn with_setup(moves: &'a Vec<Move>, setup: Setup) -> Self {
         Self {
-            current_player: Player::White,
-            board: Board::new(),
+            current_player: setup.to_move,
+            board: setup.board.clone(),
             moves,
             debug: false,
+            notation: Notation::default(),
+            names: Names::default(),
+            start: setup,
+            played: vec![],
+            in_turn: false,
         }
     }
 
@@ -48,92 +104,222 @@ impl<'a> Game<'a> {
             println!("{}", self.board);
         }
 
+        let rules = self.board.rules().clone();
+
         let mut moves_iter = self.moves.iter().peekable();
 
+        // Where the turn being played started and the moves it could be
+        let mut turn_start = self.board.clone();
+        let mut turn_moves = vec![];
+
+        // Whether the last recorded turn still needs more jumps
+        let mut must_keep_jumping = false;
+
         while let Some(mov) = moves_iter.next() {
-            if !self.board.has_legal_moves(&self.current_player) {
+            if !self.in_turn {
+                turn_start = self.board.clone();
+                turn_moves = self.board.legal_moves(&self.current_player);
+            }
+
+            if turn_moves.is_empty() {
                 break;
             }
 
             if self.debug {
-                println!("Player: {}", self.current_player);
-                println!(
-                    "Move: ({}, {}) to ({}, {})",
-                    mov.initial.x, mov.initial.y, mov.destination.x, mov.destination.y
-                );
+                println!("Player: {}", self.names.name(self.current_player));
+
+                if self.notation.algebraic {
+                    println!(
+                        "Move: {} to {}",
+                        mov.initial.notate(self.notation),
+                        mov.destination.notate(self.notation)
+                    );
+                } else {
+                    let coordinates = self.notation.coordinates;
+                    let initial = coordinates.written(mov.initial, self.notation.size);
+                    let destination = coordinates.written(mov.destination, self.notation.size);
+
+                    println!(
+                        "Move: ({}, {}) to ({}, {})",
+                        initial.x, initial.y, destination.x, destination.y
+                    );
+                }
+            }
+
+            let captured = match self.board.make_move(&self.current_player, mov) {
+                Ok(captured) => captured,
+                Err(reason) => return Validation::Illegal(mov, reason),
+            };
+
+            let mut path = match self.played.last() {
+                Some(turn) if self.in_turn => turn.path.clone(),
+                _ => vec![mov.initial],
+            };
+
+            path.push(mov.destination);
+
+            // The legal moves this turn can still turn out to be
+            let candidates: Vec<&LegalMove> = turn_moves
+                .iter()
+                .filter(|legal| legal.path.starts_with(&path))
+                .collect();
+
+            if candidates.is_empty() {
+                let capture = turn_moves
+                    .iter()
+                    .filter(|legal| legal.is_capture())
+                    .find(|legal| legal.initial() == path[0])
+                    .or_else(|| turn_moves.iter().find(|legal| legal.is_capture()));
+
+                if let Some(capture) = capture {
+                    self.discard_turn();
+
+                    return Validation::Illegal(mov, Reason::CapturePriority(capture.initial()));
+                }
             }
 
-            if !self.board.make_move(&self.current_player, mov) {
-                return Validation::Illegal(mov);
+            self.record(mov, captured);
+
+            let finished = candidates.iter().find(|legal| legal.path == path);
+
+            // The rules decide what the board looks like once the whole
+            // turn is known, e.g. when captured pieces are removed
+            if let Some(legal) = finished {
+                self.board = turn_start.clone();
+                self.board.apply(legal);
             }
 
+            must_keep_jumping = !candidates.is_empty() && finished.is_none();
+
             if self.debug {
                 println!("{}", self.board);
             }
 
             if let Some(next_mov) = moves_iter.peek() {
-                let opt_player = self
+                let piece = self
+                    .board
+                    .get(mov.destination.x as usize, mov.destination.y as usize)
+                    .expect("a piece was just moved here");
+
+                let opt_piece = self
                     .board
                     .get(next_mov.initial.x as usize, next_mov.initial.y as usize);
 
-                match opt_player {
-                    None => {
-                        return Validation::Illegal(next_mov);
-                    }
-                    Some(player) if *player == self.current_player => {
-                        if mov.destination.x != next_mov.initial.x
-                            || mov.destination.y != next_mov.initial.y
+                let rejection = match opt_piece {
+                    None if !next_mov.is_on_board(&*rules) => Some(Reason::OutOfBounds),
+                    None => Some(Reason::EmptySquare),
+                    Some(next_piece) if next_piece.player == self.current_player => {
+                        if !must_keep_jumping {
+                            Some(Reason::TurnOver)
+                        } else if !next_mov.is_on_board(&*rules) {
+                            Some(Reason::OutOfBounds)
+                        } else if mov.destination != next_mov.initial
+                            || !next_mov.is_jump(&*rules, &piece)
                         {
-                            return Validation::Illegal(next_mov);
-                        }
-
-                        if !mov.is_jump(player) || !next_mov.is_jump(player) {
-                            return Validation::Illegal(next_mov);
+                            Some(Reason::UnfinishedJump(mov.destination))
+                        } else {
+                            None
                         }
                     }
+                    _ if must_keep_jumping => Some(Reason::UnfinishedJump(mov.destination)),
                     _ => {
-                        if mov.is_jump(&self.current_player)
-                            && self.board.is_jumping_possible(
-                                &self.current_player,
-                                // Here we pretend that the next move would start where
-                                // the current one ended
-                                &Move {
-                                    initial: Position {
-                                        x: mov.destination.x,
-                                        y: mov.destination.y,
-                                    },
-                                    destination: Position { x: 0, y: 0 },
-                                    line: 0,
-                                    src: "".to_string(),
-                                },
-                            )
-                        {
-                            return Validation::Illegal(next_mov);
-                        }
+                        self.next_player();
+
+                        None
+                    }
+                };
 
-                        self.next_player()
+                if let Some(reason) = rejection {
+                    if must_keep_jumping {
+                        self.discard_turn();
                     }
+
+                    return Validation::Illegal(next_mov, reason);
                 }
             }
         }
 
+        // Only whole turns are kept, the moves ran out part way through
+        // this one
+        if must_keep_jumping {
+            self.discard_turn();
+        }
+
         if self.board.has_legal_moves(&Player::Red) && self.board.has_legal_moves(&Player::White) {
             return Validation::IncompleteGame;
         }
 
-        let red_score = self.board.red_score();
-        let white_score = self.board.white_score();
+        match rules.winner(&self.board) {
+            Some(player) => Validation::Winner(player),
+            None => Validation::Tie,
+        }
+    }
+
+    /// Adds a move that was just made to the turns played so far, jumps
+    /// continuing a multi-jump are merged into the turn they belong to
+    fn record(&mut self, mov: &Move, captured: Option<Position>) {
+        match self.played.last_mut() {
+            Some(turn) if self.in_turn => {
+                turn.path.push(mov.destination);
+                turn.captured.extend(captured);
+            }
+            _ => {
+                self.played.push(LegalMove {
+                    path: vec![mov.initial, mov.destination],
+                    captured: captured.into_iter().collect(),
+                });
 
-        if white_score == red_score {
-            Validation::Tie
-        } else if white_score > red_score {
-            Validation::Winner(Player::White)
-        } else {
-            Validation::Winner(Player::Red)
+                self.in_turn = true;
+            }
+        }
+    }
+
+    /// Drops the turn being played from `played`, for turns that are
+    /// rejected or never finished
+    fn discard_turn(&mut self) {
+        if self.in_turn {
+            self.played.pop();
+
+            self.in_turn = false;
         }
     }
 
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn current_player(&self) -> &Player {
+        &self.current_player
+    }
+
+    /// The position the game started from
+    pub fn start(&self) -> &Setup {
+        &self.start
+    }
+
+    /// Every turn `validate` accepted so far, multi-jumps as a single move
+    pub fn played(&self) -> &[LegalMove] {
+        &self.played
+    }
+
+    /// Every legal move available to the player whose turn it is
+    pub fn legal_moves(&self) -> Vec<LegalMove> {
+        self.board.legal_moves(&self.current_player)
+    }
+
+    /// Counts the positions reachable in `depth` moves, see `Board::perft`
+    pub fn perft(&self, depth: usize) -> u64 {
+        self.board.perft(&self.current_player, depth)
+    }
+
+    /// Node counts below each legal move, see `Board::divide`
+    pub fn divide(&self, depth: usize) -> Vec<(LegalMove, u64)> {
+        self.board.divide(&self.current_player, depth)
+    }
+
     fn next_player(&mut self) {
+        self.in_turn = false;
+
         if let Player::White = self.current_player {
             self.current_player = Player::Red
         } else {
@@ -144,4 +330,18 @@ impl<'a> Game<'a> {
     pub fn toggle_debug(&mut self) {
         self.debug = !self.debug;
     }
+
+    /// How squares are written in the debug output
+    pub fn set_notation(&mut self, notation: Notation) {
+        self.notation = notation;
+    }
+
+    /// What the sides are called in the debug output
+    pub fn set_names(&mut self, names: Names) {
+        self.names = names;
+    }
+
+    pub fn names(&self) -> &Names {
+        &self.names
+    }
 }
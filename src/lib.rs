@@ -1,4 +1,10 @@
-mod board;
+pub mod board;
 pub mod game;
 pub mod r#move;
-mod player;
+pub mod parser;
+pub mod pdn;
+pub mod piece;
+pub mod player;
+pub mod reason;
+pub mod rules;
+pub mod setup;
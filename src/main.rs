@@ -3,12 +3,16 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use structopt::StructOpt;
 
 use checkers::{
     game::Game,
-    r#move::{Move, Position},
+    parser::{parse_game_with_options, GameRecord, ParseOptions},
+    pdn::{is_pdn, parse_pdn_with_rules, write_pdn},
+    player::{Names, Player},
+    r#move::{Coordinates, Notation, Origin},
+    rules::{self, Rules},
 };
 
 /// Validate checker's moves
@@ -22,144 +26,600 @@ struct Opt {
     /// Print the Current Move and Board
     #[structopt(short, long)]
     debug: bool,
+
+    /// Print each validated game as PDN instead of the answer
+    #[structopt(long)]
+    pdn: bool,
+
+    /// Write squares as a1 to h8 in the debug output and illegal move answers
+    #[structopt(long)]
+    algebraic: bool,
+
+    /// The variant the games are played under
+    #[structopt(long, default_value = "american", parse(try_from_str = parse_rules))]
+    rules: Rules,
+
+    /// Read the variant from a TOML file instead, see the README
+    #[structopt(long, parse(from_os_str), conflicts_with = "rules")]
+    rules_file: Option<PathBuf>,
+
+    /// Play the variant on a board of another size, e.g. 6x6 or 10x8
+    #[structopt(long, parse(try_from_str = parse_size))]
+    size: Option<(i32, i32)>,
+
+    /// The side that moves first, by default the variant's first player
+    #[structopt(long)]
+    first: Option<String>,
+
+    /// What to call red and white, e.g. black,white
+    #[structopt(long, parse(try_from_str = parse_names))]
+    colours: Option<Names>,
+
+    /// The corner of the debug board that x,y pairs count from: top-left,
+    /// top-right, bottom-left or bottom-right
+    #[structopt(long, default_value = "top-left", parse(try_from_str = parse_origin))]
+    origin: Origin,
+
+    /// Read and write x,y pairs as y,x
+    #[structopt(long)]
+    swap_xy: bool,
+
+    /// Count x,y pairs from 1 instead of 0
+    #[structopt(long)]
+    one_based: bool,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+/// How each input is validated and what gets printed for it
+#[derive(Debug)]
+struct Config {
+    debug: bool,
+    pdn: bool,
+    notation: Notation,
+    rules: Rules,
+    /// Who moves first in files without a setup line, `None` to leave it to
+    /// the rules
+    first_player: Option<Player>,
+    names: Names,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            debug: false,
+            pdn: false,
+            notation: Notation::default(),
+            rules: rules::american(),
+            first_player: None,
+            names: Names::default(),
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// Count the positions reachable from the starting position
+    Perft {
+        /// How many moves deep to search
+        depth: usize,
+    },
 }
 
 fn main() -> Result<()> {
     let opt = Opt::from_args();
 
+    let rules = match &opt.rules_file {
+        Some(path) => read_rules_file(path)?,
+        None => opt.rules,
+    };
+
+    let rules = match opt.size {
+        Some(size) => rules::resize(rules, size)
+            .ok_or_else(|| anyhow!("can't play on a {}x{} board", size.0, size.1))?,
+        None => rules,
+    };
+
+    let names = opt.colours.unwrap_or_default();
+
+    let first_player = match &opt.first {
+        Some(name) => Some(names.player(name).ok_or_else(|| {
+            anyhow!(
+                "unknown player `{}`, expected {} or {}",
+                name,
+                names.red,
+                names.white
+            )
+        })?),
+        None => None,
+    };
+
+    let notation = Notation {
+        algebraic: opt.algebraic,
+        size: rules.size(),
+        coordinates: Coordinates {
+            origin: opt.origin,
+            swap_xy: opt.swap_xy,
+            one_based: opt.one_based,
+        },
+    };
+
+    if let Some(Command::Perft { depth }) = opt.command {
+        if !opt.input.is_empty() {
+            bail!("perft counts from the starting position and doesn't read input files");
+        }
+
+        let first_player = first_player.unwrap_or_else(|| rules.first_player());
+
+        println!("{}", perft(depth, rules, first_player, notation));
+
+        return Ok(());
+    }
+
+    let config = Config {
+        debug: opt.debug,
+        pdn: opt.pdn,
+        notation,
+        rules,
+        first_player,
+        names,
+    };
+
+    let mut failures = 0;
+
     for path in &opt.input {
-        let mut input = read_file(path)?;
+        match validate_file(path, &config) {
+            Ok(answer) => println!("{}", answer),
+            Err(err) => {
+                eprintln!("{:#}", err);
 
-        let answer = validate_input(&mut input, opt.debug)?;
+                failures += 1;
+            }
+        }
+    }
 
-        println!("{}", answer);
+    if failures > 0 {
+        bail!(
+            "{} of {} files could not be validated",
+            failures,
+            opt.input.len()
+        );
     }
 
     Ok(())
 }
 
-fn read_file(path: &Path) -> Result<String> {
-    fs::read_to_string(path)
-        .with_context(|| format!("failed to read moves from {}", path.display()))
+fn parse_rules(name: &str) -> Result<Rules> {
+    rules::from_name(name).ok_or_else(|| {
+        anyhow!(
+            "unknown rules `{}`, expected one of {}",
+            name,
+            rules::NAMES.join(", ")
+        )
+    })
 }
 
-fn validate_input(input: &mut String, debug: bool) -> Result<String> {
-    clean_input(input);
+fn parse_size(src: &str) -> Result<(i32, i32)> {
+    let invalid = || anyhow!("`{}` is not a board size like 10x8", src);
 
-    let moves = parse_moves(&input)?;
+    let (width, height) = src.split_once('x').ok_or_else(invalid)?;
 
-    let mut game = Game::new(&moves);
+    Ok((
+        width.parse().map_err(|_| invalid())?,
+        height.parse().map_err(|_| invalid())?,
+    ))
+}
 
-    if debug {
-        game.toggle_debug();
+fn parse_names(src: &str) -> Result<Names> {
+    let names = src
+        .split_once(',')
+        .map(|(red, white)| (red.trim(), white.trim()));
+
+    match names {
+        Some((red, white)) if !red.is_empty() && !white.is_empty() && red != white => {
+            Ok(Names::new(red, white))
+        }
+        _ => bail!("`{}` is not two different names like black,white", src),
     }
+}
 
-    let validation = game.validate();
+fn parse_origin(src: &str) -> Result<Origin> {
+    match src {
+        "top-left" => Ok(Origin::TopLeft),
+        "top-right" => Ok(Origin::TopRight),
+        "bottom-left" => Ok(Origin::BottomLeft),
+        "bottom-right" => Ok(Origin::BottomRight),
+        _ => bail!(
+            "unknown origin `{}`, expected top-left, top-right, bottom-left or bottom-right",
+            src
+        ),
+    }
+}
+
+fn read_rules_file(path: &Path) -> Result<Rules> {
+    let src = fs::read_to_string(path)
+        .with_context(|| format!("failed to read rules from {}", path.display()))?;
 
-    Ok(format!("{}", validation))
+    rules::from_toml(&src).with_context(|| format!("invalid rules in {}", path.display()))
 }
 
-fn clean_input(input: &mut String) {
-    while let Some('\n') = input.chars().next_back() {
-        input.pop();
-    }
+fn validate_file(path: &Path, config: &Config) -> Result<String> {
+    let input = read_file(path)?;
 
-    while let Some('\r') = input.chars().next_back() {
-        input.pop();
-    }
+    validate_input(&input, config)
+        .with_context(|| format!("failed to parse moves from {}", path.display()))
 }
 
-fn parse_moves(input: &str) -> Result<Vec<Move>> {
-    input
-        .split("\n")
-        .filter(|line| !line.is_empty())
-        .enumerate()
-        .fold(Ok(vec![]), |acc, (index, input_line)| {
-            let mut input_line = input_line.to_string();
+fn read_file(path: &Path) -> Result<String> {
+    fs::read_to_string(path)
+        .with_context(|| format!("failed to read moves from {}", path.display()))
+}
 
-            clean_input(&mut input_line);
+fn validate_input(input: &str, config: &Config) -> Result<String> {
+    if is_pdn(input) {
+        let answers = parse_pdn_with_rules(input, config.rules.clone())?
+            .into_iter()
+            .map(|pdn_game| validate_record(pdn_game.record, &pdn_game.tags, config))
+            .collect::<Vec<String>>();
 
-            let move_line = input_line
-                .split(",")
-                .map(|n| {
-                    let result = n.parse::<i32>();
+        return Ok(answers.join("\n"));
+    }
 
-                    if result.is_err() {
-                        println!("line {} illegal move: {}", index + 1, input_line);
+    let options = ParseOptions {
+        coordinates: config.notation.coordinates,
+        first_player: config.first_player,
+    };
 
-                        std::process::exit(0);
-                    }
+    let record = parse_game_with_options(input, config.rules.clone(), options)?;
 
-                    Ok(result?)
-                })
-                .collect::<Result<Vec<i32>>>()?;
+    Ok(validate_record(record, &[], config))
+}
 
-            if move_line.len() != 4 {
-                println!("line {} illegal move: {}", index + 1, input_line);
+fn validate_record(record: GameRecord, tags: &[(String, String)], config: &Config) -> String {
+    let mut game = match record.setup {
+        Some(setup) => Game::with_setup(&record.moves, setup),
+        None => Game::with_sides(
+            &record.moves,
+            config.rules.clone(),
+            config
+                .first_player
+                .unwrap_or_else(|| config.rules.first_player()),
+            config.names.clone(),
+        ),
+    };
+
+    game.set_names(config.names.clone());
+
+    if config.debug {
+        game.toggle_debug();
+    }
 
-                std::process::exit(0);
-            }
+    game.set_notation(config.notation);
 
-            let mut acc = acc?;
+    let validation = game.validate();
 
-            let initial = Position {
-                x: move_line[0],
-                y: move_line[1],
-            };
+    if config.pdn {
+        return write_pdn(&game, &validation, tags).trim_end().to_string();
+    }
+
+    validation.notate(config.notation, &config.names)
+}
+
+fn perft(depth: usize, rules: Rules, first_player: Player, notation: Notation) -> String {
+    let moves = vec![];
+
+    let game = Game::with_sides(&moves, rules, first_player, Names::default());
 
-            let destination = Position {
-                x: move_line[2],
-                y: move_line[3],
-            };
+    let divide = game.divide(depth);
 
-            acc.push(Move {
-                initial,
-                destination,
-                line: index + 1,
-                src: input_line,
-            });
+    let mut output = divide
+        .iter()
+        .map(|(mov, nodes)| format!("{}: {}\n", mov.notate(notation), nodes))
+        .collect::<String>();
 
-            Ok(acc)
-        })
+    let total: u64 = if divide.is_empty() {
+        game.perft(depth)
+    } else {
+        divide.iter().map(|(_, nodes)| nodes).sum()
+    };
+
+    output.push_str(&format!("nodes: {}", total));
+
+    output
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::validate_input;
+    use checkers::{
+        player::{Names, Player},
+        r#move::{Coordinates, Notation, Origin},
+        rules,
+    };
+
+    use crate::{perft, validate_input, Config};
 
     #[test]
     fn red() {
-        let mut input = include_str!("../inputs/red.txt").to_string();
+        let input = include_str!("../inputs/red.txt");
 
-        let answer = validate_input(&mut input, false);
+        let answer = validate_input(input, &Config::default());
 
         assert_eq!(answer.unwrap().as_str(), "red");
     }
 
     #[test]
     fn white() {
-        let mut input = include_str!("../inputs/white.txt").to_string();
+        let input = include_str!("../inputs/white.txt");
 
-        let answer = validate_input(&mut input, false);
+        let answer = validate_input(input, &Config::default());
 
         assert_eq!(answer.unwrap().as_str(), "white");
     }
 
     #[test]
     fn illegal_move() {
-        let mut input = include_str!("../inputs/illegal_move.txt").to_string();
+        let input = include_str!("../inputs/illegal_move.txt");
+
+        let answer = validate_input(input, &Config::default());
 
-        let answer = validate_input(&mut input, false);
+        assert_eq!(
+            answer.unwrap().as_str(),
+            "line 15 illegal move: 1,0,0,5 (not a diagonal move)"
+        );
+    }
+
+    #[test]
+    fn compulsory_jump() {
+        let input = include_str!("../inputs/compulsory_jump.txt");
+
+        let answer = validate_input(input, &Config::default());
+
+        assert_eq!(
+            answer.unwrap().as_str(),
+            "line 43 illegal move: 2,1,3,2 (compulsory jump available from 7,2)"
+        );
+    }
 
-        assert_eq!(answer.unwrap().as_str(), "line 15 illegal move: 1,0,0,5");
+    #[test]
+    fn algebraic() {
+        let config = Config {
+            notation: Notation {
+                algebraic: true,
+                ..Notation::default()
+            },
+            ..Config::default()
+        };
+
+        let input = include_str!("../inputs/compulsory_jump.txt");
+
+        let answer = validate_input(input, &config);
+
+        assert_eq!(
+            answer.unwrap().as_str(),
+            "line 43 illegal move: f2-e3 (compulsory jump available from a3)"
+        );
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let input = include_str!("../inputs/out_of_bounds.txt");
+
+        let answer = validate_input(input, &Config::default());
+
+        assert_eq!(
+            answer.unwrap().as_str(),
+            "line 3 illegal move: 3,2,-2147483648,0 (off the board)"
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let input = include_str!("../inputs/malformed.txt");
+
+        let answer = validate_input(input, &Config::default());
+
+        assert_eq!(
+            answer.unwrap_err().to_string(),
+            "line 3 column 5: `a` is not a number"
+        );
+    }
+
+    #[test]
+    fn setup() {
+        let input = include_str!("../inputs/setup.txt");
+
+        let answer = validate_input(input, &Config::default());
+
+        assert_eq!(answer.unwrap().as_str(), "white");
+    }
+
+    #[test]
+    fn pdn() {
+        let input = include_str!("../inputs/game.pdn");
+
+        let answer = validate_input(input, &Config::default());
+
+        assert_eq!(answer.unwrap().as_str(), "red");
+    }
+
+    #[test]
+    fn square_numbers() {
+        let input = include_str!("../inputs/squares.txt");
+
+        let answer = validate_input(input, &Config::default());
+
+        assert_eq!(answer.unwrap().as_str(), "red");
+
+        let config = Config {
+            first_player: Some(Player::White),
+            ..Config::default()
+        };
+
+        let answer = validate_input(input, &config);
+
+        assert_eq!(
+            answer.unwrap().as_str(),
+            "line 1 illegal move: 11-15 (moves the opponent's piece)"
+        );
+    }
+
+    #[test]
+    fn pdn_export() {
+        let config = Config {
+            pdn: true,
+            ..Config::default()
+        };
+
+        let exported = validate_input(include_str!("../inputs/red.txt"), &config).unwrap();
+
+        assert!(exported.contains("[Result \"0-1\"]"));
+        assert!(!exported.contains("GameType"));
+        assert!(exported.contains("5. 9x18x27 32x23"));
+
+        let answer = validate_input(&exported, &Config::default());
+
+        assert_eq!(answer.unwrap().as_str(), "red");
     }
 
     #[test]
     fn incomplete() {
-        let mut input = include_str!("../inputs/incomplete.txt").to_string();
+        let input = include_str!("../inputs/incomplete.txt");
+
+        let answer = validate_input(input, &Config::default());
+
+        assert_eq!(answer.unwrap().as_str(), "incomplete game");
+    }
+
+    #[test]
+    fn king() {
+        let input = include_str!("../inputs/king.txt");
+
+        let answer = validate_input(input, &Config::default());
+
+        assert_eq!(answer.unwrap().as_str(), "incomplete game");
+    }
+
+    #[test]
+    fn international() {
+        let config = Config {
+            rules: rules::from_name("international").unwrap(),
+            ..Config::default()
+        };
+
+        let input = include_str!("../examples/international.txt");
+
+        let answer = validate_input(input, &config);
+
+        assert_eq!(
+            answer.unwrap().as_str(),
+            "line 2 illegal move: 37x26 (the capture from 6,3 takes priority)"
+        );
+
+        let config = Config {
+            pdn: true,
+            ..config
+        };
+
+        let exported = validate_input(input, &config).unwrap();
+
+        assert!(!exported.contains("37x26"));
+    }
+
+    #[test]
+    fn antidraughts() {
+        let config = Config {
+            rules: rules::from_name("antidraughts").unwrap(),
+            ..Config::default()
+        };
+
+        let input = include_str!("../inputs/red.txt");
+
+        let answer = validate_input(input, &config);
+
+        assert_eq!(answer.unwrap().as_str(), "white");
+    }
+
+    #[test]
+    fn colour_names() {
+        let config = Config {
+            names: Names::new("black", "white"),
+            ..Config::default()
+        };
+
+        let input = include_str!("../inputs/red.txt");
+
+        let answer = validate_input(input, &config);
 
-        let answer = validate_input(&mut input, false);
+        assert_eq!(answer.unwrap().as_str(), "black");
+
+        let config = Config {
+            first_player: Some(Player::Red),
+            ..config
+        };
+
+        let answer = validate_input(input, &config);
+
+        assert_eq!(
+            answer.unwrap().as_str(),
+            "line 1 illegal move: 1,2,2,3 (moves the opponent's piece)"
+        );
+    }
+
+    #[test]
+    fn coordinates() {
+        let config = Config {
+            notation: Notation {
+                coordinates: Coordinates {
+                    origin: Origin::BottomLeft,
+                    swap_xy: true,
+                    one_based: true,
+                },
+                ..Notation::default()
+            },
+            ..Config::default()
+        };
+
+        let input = include_str!("../examples/bottom_left.txt");
+
+        let answer = validate_input(input, &config);
+
+        assert_eq!(
+            answer.unwrap().as_str(),
+            "line 43 illegal move: 7,3,6,4 (compulsory jump available from 6,8)"
+        );
+    }
+
+    #[test]
+    fn rules_file() {
+        let config = Config {
+            rules: rules::from_toml(include_str!("../examples/house_rules.toml")).unwrap(),
+            ..Config::default()
+        };
+
+        let input = include_str!("../examples/optional_jump.txt");
+
+        let answer = validate_input(input, &config);
 
         assert_eq!(answer.unwrap().as_str(), "incomplete game");
+
+        let answer = validate_input(input, &Config::default());
+
+        assert_eq!(
+            answer.unwrap().as_str(),
+            "line 2 illegal move: 1,2,0,3 (compulsory jump available from 1,2)"
+        );
+    }
+
+    #[test]
+    fn perft_divide() {
+        let answer = perft(2, rules::american(), Player::White, Notation::default());
+
+        assert!(answer.starts_with("1,2-2,3: 7\n"));
+        assert!(answer.ends_with("nodes: 49"));
+
+        let answer = perft(1, rules::american(), Player::Red, Notation::default());
+
+        assert!(answer.starts_with("0,5-1,4: 1\n"));
     }
 }
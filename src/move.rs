@@ -1,11 +1,192 @@
-use crate::player::Player;
+use std::{convert::TryFrom, fmt};
 
-#[derive(Debug)]
+use crate::{piece::Piece, rules::RuleSet};
+
+/// How squares are written in moves and messages
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Notation {
+    /// Files from `a` and ranks from `1`, `a1` being white's leftmost dark
+    /// square, instead of zero based `x,y` pairs
+    pub algebraic: bool,
+    /// The width and height of the board, which the files depend on
+    pub size: (i32, i32),
+    /// How `x,y` pairs are written when they aren't algebraic
+    pub coordinates: Coordinates,
+}
+
+impl Default for Notation {
+    fn default() -> Self {
+        Self {
+            algebraic: false,
+            size: (8, 8),
+            coordinates: Coordinates::default(),
+        }
+    }
+}
+
+/// The corner of the board, as printed by `--debug`, that `x,y` pairs count
+/// from. `TopLeft` is the layout the rules use, with white's back row at the
+/// top.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum Origin {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// How the `x,y` pairs of an input file map onto the board, for files from
+/// recorders that count from another corner, write the row first or start
+/// at 1
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub struct Coordinates {
+    pub origin: Origin,
+    /// Pairs are written `y,x`
+    pub swap_xy: bool,
+    /// The first row and column are 1 instead of 0
+    pub one_based: bool,
+}
+
+impl Coordinates {
+    /// The square a pair written in these coordinates refers to on a board
+    /// of the given width and height. Pairs too far out to be worked out
+    /// are put just off the board.
+    pub fn position(&self, written: Position, (width, height): (i32, i32)) -> Position {
+        let offset = if self.one_based { 1 } else { 0 };
+
+        // Widened so that pairs near the limits of `i32` can't overflow
+        let (column, row) = if self.swap_xy {
+            (i64::from(written.y) - offset, i64::from(written.x) - offset)
+        } else {
+            (i64::from(written.x) - offset, i64::from(written.y) - offset)
+        };
+
+        let (from_right, from_bottom) = match self.origin {
+            Origin::TopLeft => (false, false),
+            Origin::TopRight => (true, false),
+            Origin::BottomLeft => (false, true),
+            Origin::BottomRight => (true, true),
+        };
+
+        let x = if from_right {
+            i64::from(width) - 1 - column
+        } else {
+            column
+        };
+
+        let y = if from_bottom {
+            i64::from(height) - 1 - row
+        } else {
+            row
+        };
+
+        let off_board = |n: i64| i32::try_from(n).unwrap_or(-1);
+
+        Position {
+            x: off_board(x),
+            y: off_board(y),
+        }
+    }
+
+    /// How `position` is written in these coordinates, the inverse of
+    /// `Coordinates::position`
+    pub fn written(&self, position: Position, size: (i32, i32)) -> Position {
+        // Mirroring is its own inverse, so only the swap and the offset
+        // have to be undone in the other order
+        let Position { x, y } = Self {
+            one_based: false,
+            swap_xy: false,
+            ..*self
+        }
+        .position(position, size);
+
+        let offset = if self.one_based { 1 } else { 0 };
+
+        if self.swap_xy {
+            Position {
+                x: y.saturating_add(offset),
+                y: x.saturating_add(offset),
+            }
+        } else {
+            Position {
+                x: x.saturating_add(offset),
+                y: y.saturating_add(offset),
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
 }
 
+impl Position {
+    /// The position of an algebraic square name such as `c3` on an 8x8
+    /// board, files run from high `x` to low `x` so that `a1` lines up with
+    /// square 29
+    pub fn from_algebraic(src: &str) -> Option<Self> {
+        Self::from_algebraic_on(src, (8, 8))
+    }
+
+    /// Same as `from_algebraic` on a board of the given width and height
+    pub fn from_algebraic_on(src: &str, (width, height): (i32, i32)) -> Option<Self> {
+        let mut chars = src.chars();
+
+        let file = chars.next().filter(char::is_ascii_lowercase)? as i32 - 'a' as i32;
+        let rank = chars.as_str().parse::<i32>().ok()?;
+
+        if file >= width || !(1..=height).contains(&rank) {
+            return None;
+        }
+
+        Some(Self {
+            x: width - 1 - file,
+            y: rank - 1,
+        })
+    }
+
+    /// The algebraic name of this square on an 8x8 board, `None` when it
+    /// is off the board
+    pub fn algebraic(&self) -> Option<String> {
+        self.algebraic_on((8, 8))
+    }
+
+    /// Same as `algebraic` on a board of the given width and height
+    pub fn algebraic_on(&self, (width, height): (i32, i32)) -> Option<String> {
+        if !(0..width).contains(&self.x) || !(0..height).contains(&self.y) {
+            return None;
+        }
+
+        let file = (b'a' + (width - 1 - self.x) as u8) as char;
+
+        Some(format!("{}{}", file, self.y + 1))
+    }
+
+    /// Writes the position in the given notation, squares off the board
+    /// are always written as coordinates
+    pub fn notate(&self, notation: Notation) -> String {
+        if notation.algebraic {
+            if let Some(name) = self.algebraic_on(notation.size) {
+                return name;
+            }
+        }
+
+        notation
+            .coordinates
+            .written(*self, notation.size)
+            .to_string()
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
 #[derive(Debug)]
 pub struct Move {
     pub initial: Position,
@@ -14,59 +195,124 @@ pub struct Move {
     pub src: String,
 }
 
+/// A complete turn for one side, either a single step or a whole
+/// sequence of jumps made by the same piece
+#[derive(Debug, PartialEq, Clone)]
+pub struct LegalMove {
+    pub path: Vec<Position>,
+    pub captured: Vec<Position>,
+}
+
+impl LegalMove {
+    pub fn initial(&self) -> Position {
+        self.path[0]
+    }
+
+    pub fn destination(&self) -> Position {
+        self.path[self.path.len() - 1]
+    }
+
+    pub fn is_capture(&self) -> bool {
+        !self.captured.is_empty()
+    }
+
+    /// Writes the squares of the move in the given notation, joined by `x`
+    /// for captures and `-` otherwise
+    pub fn notate(&self, notation: Notation) -> String {
+        let separator = if self.is_capture() { "x" } else { "-" };
+
+        self.path
+            .iter()
+            .map(|position| position.notate(notation))
+            .collect::<Vec<String>>()
+            .join(separator)
+    }
+}
+
+impl fmt::Display for LegalMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.notate(Notation::default()))
+    }
+}
+
 impl Move {
-    pub fn is_valid(&self, player: &Player) -> bool {
-        if self.initial.x > 7
-            || self.initial.x < 0
-            || self.initial.y > 7
-            || self.initial.y < 0
-            || self.destination.x > 7
-            || self.destination.x < 0
-            || self.destination.y > 7
-            || self.destination.y < 0
-        {
-            return false;
+    /// The move as it was written for coordinates, otherwise its two
+    /// squares in the given notation
+    pub fn notate(&self, notation: Notation) -> String {
+        if !notation.algebraic {
+            return self.src.clone();
         }
 
+        format!(
+            "{}-{}",
+            self.initial.notate(notation),
+            self.destination.notate(notation)
+        )
+    }
+
+    /// The unit step leading from `initial` to `destination` and how many
+    /// of them it takes, `None` unless both are on the same diagonal, row or
+    /// column
+    pub fn direction(&self) -> Option<((i32, i32), i32)> {
         let x_diff = self.destination.x - self.initial.x;
         let y_diff = self.destination.y - self.initial.y;
 
-        match player {
-            Player::Red => {
-                (x_diff.abs() == 1 && y_diff == -1) || (x_diff.abs() == 2 && y_diff == -2)
-            }
-            Player::White => {
-                (x_diff.abs() == 1 && y_diff == 1) || (x_diff.abs() == 2 && y_diff == 2)
-            }
+        let distance = x_diff.abs().max(y_diff.abs());
+
+        if distance == 0 || (x_diff != 0 && y_diff != 0 && x_diff.abs() != y_diff.abs()) {
+            return None;
         }
+
+        Some(((x_diff.signum(), y_diff.signum()), distance))
+    }
+
+    pub fn is_on_board(&self, rules: &dyn RuleSet) -> bool {
+        let (width, height) = rules.size();
+
+        [&self.initial, &self.destination]
+            .iter()
+            .all(|Position { x, y }| (0..width).contains(x) && (0..height).contains(y))
+    }
+
+    /// Whether `piece` may make this move under `rules`, either a step or a
+    /// jump, ignoring what else is on the board
+    pub fn is_valid(&self, rules: &dyn RuleSet, piece: &Piece) -> bool {
+        self.is_on_board(rules) && (self.is_step(rules, piece) || self.is_jump(rules, piece))
     }
 
-    pub fn is_jump(&self, player: &Player) -> bool {
+    /// The one of `directions` leading from `initial` to `destination` and
+    /// how many steps of it that takes. A direction may be longer than a
+    /// square, e.g. `(2, 0)` for pieces that skip the light squares of a row.
+    pub fn steps_along(&self, directions: &[(i32, i32)]) -> Option<((i32, i32), i32)> {
         let x_diff = self.destination.x - self.initial.x;
         let y_diff = self.destination.y - self.initial.y;
 
-        match player {
-            Player::Red => x_diff.abs() == 2 && y_diff == -2,
-            Player::White => x_diff.abs() == 2 && y_diff == 2,
-        }
-    }
+        directions.iter().find_map(|&(dx, dy)| {
+            let steps = if dx != 0 { x_diff / dx } else { y_diff / dy };
 
-    pub fn jumped_position(&self, player: &Player) -> Option<Position> {
-        if self.is_jump(player) {
-            let x = if self.destination.x > self.initial.x {
-                self.initial.x + 1
+            if steps > 0 && (dx * steps, dy * steps) == (x_diff, y_diff) {
+                Some(((dx, dy), steps))
             } else {
-                self.initial.x - 1
-            };
+                None
+            }
+        })
+    }
 
-            let y = match player {
-                Player::Red => self.initial.y - 1,
-                Player::White => self.initial.y + 1,
-            };
+    /// Whether this is shaped like a move `piece` makes without capturing,
+    /// one step or, for a flying king, any number of them
+    pub fn is_step(&self, rules: &dyn RuleSet, piece: &Piece) -> bool {
+        match self.steps_along(&rules.move_directions(piece)) {
+            Some((_, steps)) => steps == 1 || rules.is_flying(piece),
+            None => false,
+        }
+    }
 
-            Some(Position { x, y })
-        } else {
-            None
+    /// Whether this is shaped like a jump by `piece`, two steps or, for a
+    /// flying king, two or more
+    pub fn is_jump(&self, rules: &dyn RuleSet, piece: &Piece) -> bool {
+        match self.steps_along(&rules.capture_directions(piece)) {
+            Some((_, steps)) => steps == 2 || (steps > 2 && rules.is_flying(piece)),
+            None => false,
         }
     }
 }
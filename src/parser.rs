@@ -0,0 +1,513 @@
+use std::{error::Error, fmt, io::BufRead, str::FromStr};
+
+use crate::{
+    pdn::{expand_moves, parse_squares, pdn_start, PdnMove},
+    player::Player,
+    r#move::{Coordinates, Move, Position},
+    rules::{self, Rules},
+    setup::{Setup, SetupError},
+};
+
+/// Everything read from an input file
+#[derive(Debug)]
+pub struct GameRecord {
+    /// Where the game starts from, `None` for the usual starting position
+    pub setup: Option<Setup>,
+    pub moves: Vec<Move>,
+}
+
+/// How a move file is read, besides the rules it is played under
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ParseOptions {
+    /// How the `x,y` pairs of moves and the setup line are written, square
+    /// numbers and algebraic names are the same whatever the coordinates
+    pub coordinates: Coordinates,
+    /// The side to move first in square number files without a setup
+    /// line, by default the rules' PDN first player
+    pub first_player: Option<Player>,
+}
+
+/// A line of input that couldn't be turned into a move
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    InvalidNumber(String),
+    WrongFieldCount(usize),
+    InvalidSetup(SetupError),
+    InvalidMove(String),
+    InvalidSquare(String),
+    InvalidTag,
+    UnterminatedComment,
+    Io(std::io::Error),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} column {}: ", self.line, self.column)?;
+
+        match &self.kind {
+            ParseErrorKind::InvalidNumber(field) => write!(f, "`{}` is not a number", field),
+            ParseErrorKind::WrongFieldCount(count) => {
+                write!(f, "expected 4 numbers but found {}", count)
+            }
+            ParseErrorKind::InvalidSetup(err) => write!(f, "{}", err),
+            ParseErrorKind::InvalidMove(mov) => write!(f, "`{}` is not a move", mov),
+            ParseErrorKind::InvalidSquare(square) => write!(f, "`{}` is not a square", square),
+            ParseErrorKind::InvalidTag => write!(f, "malformed tag pair"),
+            ParseErrorKind::UnterminatedComment => write!(f, "comment is never closed"),
+            ParseErrorKind::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses a single `x,y,x,y` line, the move is reported as being on line 1
+impl FromStr for Move {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        parse_move(src, 1)
+    }
+}
+
+/// Parses either an `x,y` pair or an algebraic square name like `c3`
+impl FromStr for Position {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        parse_position(src, (8, 8), Coordinates::default()).ok_or_else(|| ParseError {
+            line: 1,
+            column: 1,
+            kind: ParseErrorKind::InvalidSquare(src.to_string()),
+        })
+    }
+}
+
+/// An `x,y` pair in the given coordinates or the algebraic name of a square
+/// on a board of `size`
+pub(crate) fn parse_position(
+    src: &str,
+    size: (i32, i32),
+    coordinates: Coordinates,
+) -> Option<Position> {
+    match src.split_once(',') {
+        Some((x, y)) => {
+            let written = Position {
+                x: x.trim().parse().ok()?,
+                y: y.trim().parse().ok()?,
+            };
+
+            Some(coordinates.position(written, size))
+        }
+        None => Position::from_algebraic_on(src.trim(), size),
+    }
+}
+
+/// The prefix of the optional first line describing the starting position
+const SETUP_HEADER: &str = "setup:";
+
+/// Parses a whole game, one move per line, optionally preceded by a
+/// `setup: <position>` line (see `Setup`). Blank lines are skipped and
+/// don't count towards the line numbers.
+///
+/// Moves are either `x,y,x,y` coordinates or standard 1 to 32 square numbers
+/// (`11-15`, `22x15`, `9x18x27`), whichever the first move uses is expected
+/// throughout the file. Games in square numbers start with red (black) to
+/// move unless there is a setup line, like they do in PDN. Algebraic moves
+/// like `c3-d4` or `c3xe5xg7` are accepted as well, see
+/// `Position::from_algebraic`.
+pub fn parse_game(input: &str) -> Result<GameRecord, ParseError> {
+    parse_game_with_rules(input, rules::american())
+}
+
+/// Same as `parse_game` for a game played with other rules, square numbers
+/// and algebraic names follow the size of their board
+pub fn parse_game_with_rules(input: &str, rules: Rules) -> Result<GameRecord, ParseError> {
+    parse_game_with_options(input, rules, ParseOptions::default())
+}
+
+/// Same as `parse_game_with_rules` for a file read with other options, see
+/// `ParseOptions`
+pub fn parse_game_with_options(
+    input: &str,
+    rules: Rules,
+    options: ParseOptions,
+) -> Result<GameRecord, ParseError> {
+    let mut parser = LineParser::new(rules, options);
+
+    for line in input.lines() {
+        parser.parse_line(line)?;
+    }
+
+    Ok(parser.finish())
+}
+
+/// Same as `parse_game` but reads the lines as they come
+pub fn read_game(reader: impl BufRead) -> Result<GameRecord, ParseError> {
+    read_game_with_rules(reader, rules::american())
+}
+
+/// Same as `read_game` for a game played with other rules
+pub fn read_game_with_rules(reader: impl BufRead, rules: Rules) -> Result<GameRecord, ParseError> {
+    read_game_with_options(reader, rules, ParseOptions::default())
+}
+
+/// Same as `read_game` with the rules and options of
+/// `parse_game_with_options`
+pub fn read_game_with_options(
+    reader: impl BufRead,
+    rules: Rules,
+    options: ParseOptions,
+) -> Result<GameRecord, ParseError> {
+    let mut parser = LineParser::new(rules, options);
+
+    for result in reader.lines() {
+        let line = result.map_err(|err| ParseError {
+            line: parser.line + 1,
+            column: 1,
+            kind: ParseErrorKind::Io(err),
+        })?;
+
+        parser.parse_line(&line)?;
+    }
+
+    Ok(parser.finish())
+}
+
+#[derive(PartialEq)]
+enum Format {
+    Coordinates,
+    Squares,
+    Algebraic,
+}
+
+/// Builds up a `GameRecord` one line at a time
+struct LineParser {
+    rules: Rules,
+    options: ParseOptions,
+    line: usize,
+    format: Option<Format>,
+    setup: Option<Setup>,
+    moves: Vec<Move>,
+    numbered_moves: Vec<PdnMove>,
+}
+
+impl LineParser {
+    fn new(rules: Rules, options: ParseOptions) -> Self {
+        Self {
+            rules,
+            options,
+            line: 0,
+            format: None,
+            setup: None,
+            moves: vec![],
+            numbered_moves: vec![],
+        }
+    }
+
+    fn parse_line(&mut self, src: &str) -> Result<(), ParseError> {
+        let src = src.trim_end_matches('\r');
+
+        if src.is_empty() {
+            return Ok(());
+        }
+
+        self.line += 1;
+
+        let line = self.line;
+
+        if let Some(setup) = src.strip_prefix(SETUP_HEADER) {
+            if line == 1 {
+                let setup = Setup::parse_with_coordinates(
+                    setup,
+                    self.rules.clone(),
+                    self.options.coordinates,
+                )
+                .map_err(|err| ParseError {
+                    line,
+                    column: SETUP_HEADER.len() + 1,
+                    kind: ParseErrorKind::InvalidSetup(err),
+                })?;
+
+                self.setup = Some(setup);
+
+                return Ok(());
+            }
+        }
+
+        let format = self.format.get_or_insert(if src.contains(',') {
+            Format::Coordinates
+        } else if src.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            Format::Algebraic
+        } else {
+            Format::Squares
+        });
+
+        match format {
+            Format::Coordinates => {
+                let mut mov = parse_move(src, line)?;
+                let size = self.rules.size();
+
+                mov.initial = self.options.coordinates.position(mov.initial, size);
+                mov.destination = self.options.coordinates.position(mov.destination, size);
+
+                self.moves.push(mov);
+            }
+            Format::Algebraic => {
+                let moves = parse_algebraic_move(src, line, self.rules.size())?;
+
+                self.moves.extend(moves);
+            }
+            Format::Squares => {
+                let squares = parse_squares(src, &*self.rules).ok_or_else(|| ParseError {
+                    line,
+                    column: 1,
+                    kind: ParseErrorKind::InvalidMove(src.to_string()),
+                })?;
+
+                self.numbered_moves.push(PdnMove {
+                    squares,
+                    src: src.to_string(),
+                    line,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) -> GameRecord {
+        if self.format != Some(Format::Squares) {
+            return GameRecord {
+                setup: self.setup,
+                moves: self.moves,
+            };
+        }
+
+        let rules = self.rules;
+        let first_player = self.options.first_player;
+
+        let setup = self.setup.unwrap_or_else(|| {
+            let mut setup = pdn_start(rules);
+
+            setup.to_move = first_player.unwrap_or(setup.to_move);
+
+            setup
+        });
+
+        GameRecord {
+            moves: expand_moves(&setup, &self.numbered_moves),
+            setup: Some(setup),
+        }
+    }
+}
+
+fn parse_move(src: &str, line: usize) -> Result<Move, ParseError> {
+    let mut column = 1;
+
+    let numbers = src
+        .split(',')
+        .map(|n| {
+            let field_column = column;
+
+            column += n.chars().count() + 1;
+
+            n.parse::<i32>().map_err(|_| ParseError {
+                line,
+                column: field_column,
+                kind: ParseErrorKind::InvalidNumber(n.to_string()),
+            })
+        })
+        .collect::<Result<Vec<i32>, ParseError>>()?;
+
+    if numbers.len() != 4 {
+        return Err(ParseError {
+            line,
+            column: 1,
+            kind: ParseErrorKind::WrongFieldCount(numbers.len()),
+        });
+    }
+
+    Ok(Move {
+        initial: Position {
+            x: numbers[0],
+            y: numbers[1],
+        },
+        destination: Position {
+            x: numbers[2],
+            y: numbers[3],
+        },
+        line,
+        src: src.to_string(),
+    })
+}
+
+/// Parses a move like `c3-d4` or `c3xe5xg7`, one `Move` per hop
+fn parse_algebraic_move(src: &str, line: usize, size: (i32, i32)) -> Result<Vec<Move>, ParseError> {
+    let mut column = 1;
+
+    let squares = src
+        .split(['-', 'x'])
+        .map(|square| {
+            let square_column = column;
+
+            column += square.chars().count() + 1;
+
+            Position::from_algebraic_on(square, size).ok_or_else(|| ParseError {
+                line,
+                column: square_column,
+                kind: ParseErrorKind::InvalidSquare(square.to_string()),
+            })
+        })
+        .collect::<Result<Vec<Position>, ParseError>>()?;
+
+    if squares.len() < 2 {
+        return Err(ParseError {
+            line,
+            column: 1,
+            kind: ParseErrorKind::InvalidMove(src.to_string()),
+        });
+    }
+
+    Ok(squares
+        .windows(2)
+        .map(|hop| Move {
+            initial: hop[0],
+            destination: hop[1],
+            line,
+            src: src.to_string(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{piece::Piece, player::Player, r#move::Origin};
+
+    #[test]
+    fn move_from_str() {
+        let mov: Move = "1,2,0,3".parse().unwrap();
+
+        assert_eq!(mov.initial, Position { x: 1, y: 2 });
+        assert_eq!(mov.destination, Position { x: 0, y: 3 });
+        assert_eq!(mov.src, "1,2,0,3");
+
+        let err = "1,2,0".parse::<Move>().unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "line 1 column 1: expected 4 numbers but found 3"
+        );
+    }
+
+    #[test]
+    fn square_numbers() {
+        let record = parse_game("11-15\n23-19\n8-11\n").unwrap();
+
+        assert_eq!(record.setup.unwrap().to_move, Player::Red);
+        assert_eq!(record.moves.len(), 3);
+        assert_eq!(
+            record.moves[1].initial,
+            rules::american().square_position(23).unwrap()
+        );
+
+        let err = parse_game("11-15\n23,19\n").unwrap_err();
+
+        assert_eq!(err.to_string(), "line 2 column 1: `23,19` is not a move");
+    }
+
+    #[test]
+    fn algebraic() {
+        assert_eq!("c3".parse::<Position>().unwrap(), Position { x: 5, y: 2 });
+        assert_eq!("5,2".parse::<Position>().unwrap(), Position { x: 5, y: 2 });
+        assert_eq!(
+            rules::american()
+                .square_position(29)
+                .unwrap()
+                .algebraic()
+                .unwrap(),
+            "a1"
+        );
+
+        let record = parse_game("c3-d4\nf6-e5\nd4xf6\n").unwrap();
+
+        assert_eq!(record.moves[2].initial, Position { x: 4, y: 3 });
+        assert_eq!(record.moves[2].destination, Position { x: 2, y: 5 });
+
+        let err = parse_game("c3-d4\nf6-e9\n").unwrap_err();
+
+        assert_eq!(err.to_string(), "line 2 column 4: `e9` is not a square");
+    }
+
+    #[test]
+    fn other_coordinates() {
+        let coordinates = Coordinates {
+            origin: Origin::TopRight,
+            swap_xy: false,
+            one_based: true,
+        };
+
+        let input = "setup: white 7,3; red 6,4 c3\n7,3,5,5\n";
+
+        let options = ParseOptions {
+            coordinates,
+            ..ParseOptions::default()
+        };
+
+        let record = parse_game_with_options(input, rules::american(), options).unwrap();
+        let board = record.setup.unwrap().board;
+
+        assert_eq!(board.get(1, 2), Some(Piece::man(Player::White)));
+        assert_eq!(board.get(2, 3), Some(Piece::man(Player::Red)));
+        assert_eq!(board.get(5, 2), Some(Piece::man(Player::Red)));
+        assert_eq!(record.moves[0].initial, Position { x: 1, y: 2 });
+        assert_eq!(record.moves[0].destination, Position { x: 3, y: 4 });
+        assert_eq!(
+            coordinates.written(Position { x: 3, y: 4 }, (8, 8)),
+            Position { x: 5, y: 5 }
+        );
+    }
+
+    #[test]
+    fn extreme_coordinates() {
+        let on_board =
+            |position: Position| (0..8).contains(&position.x) && (0..8).contains(&position.y);
+
+        for origin in [Origin::TopLeft, Origin::BottomRight] {
+            for one_based in [false, true] {
+                let coordinates = Coordinates {
+                    origin,
+                    swap_xy: true,
+                    one_based,
+                };
+
+                for n in [i32::MIN, i32::MAX] {
+                    let position = coordinates.position(Position { x: n, y: 0 }, (8, 8));
+
+                    assert!(!on_board(position));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn read_game_matches_parse_game() {
+        let input = "1,2,0,3\r\n\r\n4,5,3,4\n";
+
+        let parsed = parse_game(input).unwrap().moves;
+        let read = read_game(input.as_bytes()).unwrap().moves;
+
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[1].line, 2);
+        assert_eq!(read[1].src, parsed[1].src);
+    }
+}
@@ -0,0 +1,597 @@
+use crate::{
+    board::Board,
+    game::{Game, Validation},
+    parser::{GameRecord, ParseError, ParseErrorKind},
+    player::Player,
+    r#move::{LegalMove, Move, Position},
+    rules::{self, RuleSet, Rules},
+    setup::Setup,
+};
+
+/// A game read from a Portable Draughts Notation file
+#[derive(Debug)]
+pub struct PdnGame {
+    /// The tag pairs in the order they appear, e.g. `("Event", "Club match")`
+    pub tags: Vec<(String, String)>,
+    /// The position from the `FEN` tag, or the usual start with red (black)
+    /// to move, along with every jump of every move
+    pub record: GameRecord,
+    /// The result written at the end of the move text, e.g. `1-0`
+    pub result: Option<String>,
+}
+
+impl PdnGame {
+    /// The value of the first tag called `name`
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+const RESULTS: [&str; 8] = ["1-0", "0-1", "1/2-1/2", "2-0", "0-2", "1-1", "0-0", "*"];
+
+/// Whether `input` looks like PDN rather than one `x,y,x,y` move per line
+pub fn is_pdn(input: &str) -> bool {
+    let first = input.trim_start();
+
+    first.starts_with('[')
+        || first.starts_with('{')
+        || first.split_whitespace().next().is_some_and(|word| {
+            word.ends_with('.') && word[..word.len() - 1].parse::<u32>().is_ok()
+        })
+}
+
+/// Reads every game in a PDN file of American checkers games.
+///
+/// Moves are written with the standard 1 to 32 square numbers (`11-15`,
+/// `22x15`, `15x24x31`). Comments, variations, move numbers, NAGs and move
+/// strength marks are skipped. A capture written with only its first and last
+/// squares is filled in by replaying the game so every jump becomes its own
+/// `Move`.
+pub fn parse_pdn(input: &str) -> Result<Vec<PdnGame>, ParseError> {
+    parse_pdn_with_rules(input, rules::american())
+}
+
+/// Same as `parse_pdn` for games played with other rules, squares are
+/// numbered as in `RuleSet::square_position`
+pub fn parse_pdn_with_rules(input: &str, rules: Rules) -> Result<Vec<PdnGame>, ParseError> {
+    let mut games = vec![];
+    let mut builder = GameBuilder::new(rules.clone());
+
+    for token in tokenize(input)? {
+        match token {
+            Token::Tag(name, value, line, column) => {
+                if builder.in_movetext() {
+                    games.push(builder.finish()?);
+
+                    builder = GameBuilder::new(rules.clone());
+                }
+
+                builder.tags.push((name, value, line, column));
+            }
+            Token::Word(word, line, column) => {
+                let word = strip_move_number(&word);
+
+                if word.is_empty() || word.starts_with('$') {
+                    continue;
+                }
+
+                if RESULTS.contains(&word) {
+                    builder.result = Some(word.to_string());
+
+                    games.push(builder.finish()?);
+
+                    builder = GameBuilder::new(rules.clone());
+
+                    continue;
+                }
+
+                let squares = parse_squares(word, &*rules).ok_or_else(|| ParseError {
+                    line,
+                    column,
+                    kind: ParseErrorKind::InvalidMove(word.to_string()),
+                })?;
+
+                builder.moves.push(PdnMove {
+                    squares,
+                    src: word.to_string(),
+                    line,
+                });
+            }
+        }
+    }
+
+    if builder.in_movetext() || !builder.tags.is_empty() {
+        games.push(builder.finish()?);
+    }
+
+    Ok(games)
+}
+
+/// Tags `write_pdn` always writes itself
+const OWN_TAGS: [&str; 6] = ["Event", "White", "Black", "Result", "GameType", "FEN"];
+
+/// The `GameType` of games without the tag, English draughts
+const DEFAULT_GAME_TYPE: u32 = 21;
+
+/// How long a line of move text gets before wrapping
+const LINE_WIDTH: usize = 80;
+
+/// Writes a game `Game::validate` has been run on as PDN.
+///
+/// `tags` are copied over, usually from the file the game was read from.
+/// `Event`, `White` and `Black` fall back to `?`, `Result` comes from
+/// `validation`, a `GameType` tag is added for rules other than American
+/// checkers and a `FEN` tag when the game didn't start from the usual
+/// position, with red (black) to move in American checkers. Only the turns
+/// `validate` accepted are written, so an illegal game ends with `*`.
+pub fn write_pdn(game: &Game, validation: &Validation, tags: &[(String, String)]) -> String {
+    let result = match validation {
+        Validation::Winner(Player::White) => "1-0",
+        Validation::Winner(Player::Red) => "0-1",
+        Validation::Tie => "1/2-1/2",
+        Validation::IncompleteGame | Validation::Illegal(..) => "*",
+    };
+
+    let mut output = String::new();
+
+    for name in &OWN_TAGS[..3] {
+        let value = tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map_or("?", |(_, value)| value.as_str());
+
+        output.push_str(&tag_pair(name, value));
+    }
+
+    for (name, value) in tags {
+        if !OWN_TAGS.contains(&name.as_str()) {
+            output.push_str(&tag_pair(name, value));
+        }
+    }
+
+    output.push_str(&tag_pair("Result", result));
+
+    let start = game.start();
+    let rules = start.board.rules();
+
+    if let Some(game_type) = rules
+        .game_type()
+        .filter(|game_type| *game_type != DEFAULT_GAME_TYPE)
+    {
+        output.push_str(&tag_pair("GameType", &game_type.to_string()));
+    }
+
+    let standard = pdn_start(rules.clone());
+
+    if start.to_move != standard.to_move || start.board != standard.board {
+        output.push_str(&tag_pair("FEN", &start.to_fen()));
+    }
+
+    output.push('\n');
+
+    let mut words = vec![];
+    let mut number = 1;
+    let mut player = start.to_move;
+
+    let first = rules.pdn_first_player();
+
+    for (index, mov) in game.played().iter().enumerate() {
+        if player == first {
+            words.push(format!("{}.", number));
+        } else if index == 0 {
+            words.push(format!("{}...", number));
+        }
+
+        words.push(notation(mov, &**rules));
+
+        if player != first {
+            number += 1;
+        }
+
+        player = player.opponent();
+    }
+
+    words.push(result.to_string());
+
+    let mut line = String::new();
+
+    for word in words {
+        if !line.is_empty() && line.len() + 1 + word.len() > LINE_WIDTH {
+            output.push_str(&line);
+            output.push('\n');
+
+            line.clear();
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+
+        line.push_str(&word);
+    }
+
+    output.push_str(&line);
+    output.push('\n');
+
+    output
+}
+
+fn tag_pair(name: &str, value: &str) -> String {
+    let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+
+    format!("[{} \"{}\"]\n", name, value)
+}
+
+/// A move in standard notation, e.g. `11-15` or `15x24x31`
+fn notation(mov: &LegalMove, rules: &dyn RuleSet) -> String {
+    let separator = if mov.is_capture() { "x" } else { "-" };
+
+    mov.path
+        .iter()
+        .filter_map(|position| rules.square_number(*position))
+        .map(|square| square.to_string())
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+enum Token {
+    Tag(String, String, usize, usize),
+    Word(String, usize, usize),
+}
+
+pub(crate) struct PdnMove {
+    pub(crate) squares: Vec<Position>,
+    pub(crate) src: String,
+    pub(crate) line: usize,
+}
+
+/// The usual starting position of `rules`, where PDN games start unless
+/// they have a `FEN` tag, see `RuleSet::pdn_first_player`
+pub(crate) fn pdn_start(rules: Rules) -> Setup {
+    Setup {
+        to_move: rules.pdn_first_player(),
+        board: Board::with_rules(rules),
+    }
+}
+
+struct GameBuilder {
+    rules: Rules,
+    tags: Vec<(String, String, usize, usize)>,
+    moves: Vec<PdnMove>,
+    result: Option<String>,
+}
+
+impl GameBuilder {
+    fn new(rules: Rules) -> Self {
+        Self {
+            rules,
+            tags: vec![],
+            moves: vec![],
+            result: None,
+        }
+    }
+
+    fn in_movetext(&self) -> bool {
+        !self.moves.is_empty() || self.result.is_some()
+    }
+
+    fn finish(self) -> Result<PdnGame, ParseError> {
+        let fen = self.tags.iter().find(|(name, ..)| name == "FEN");
+
+        let setup = match fen {
+            Some((_, value, line, column)) => Setup::from_fen_with_rules(value, self.rules.clone())
+                .map_err(|err| ParseError {
+                    line: *line,
+                    column: *column,
+                    kind: ParseErrorKind::InvalidSetup(err),
+                })?,
+            None => pdn_start(self.rules.clone()),
+        };
+
+        let moves = expand_moves(&setup, &self.moves);
+
+        Ok(PdnGame {
+            tags: self
+                .tags
+                .into_iter()
+                .map(|(name, value, ..)| (name, value))
+                .collect(),
+            record: GameRecord {
+                setup: Some(setup),
+                moves,
+            },
+            result: self.result,
+        })
+    }
+}
+
+/// Turns every PDN move into one `Move` per jump. The game is replayed
+/// alongside so captures that only list their first and last squares can be
+/// filled in, once a move doesn't match any legal move the rest are taken
+/// literally and left for `Game::validate` to report.
+pub(crate) fn expand_moves(setup: &Setup, pdn_moves: &[PdnMove]) -> Vec<Move> {
+    let mut replay = Some((setup.board.clone(), setup.to_move));
+    let mut moves = vec![];
+
+    for pdn_move in pdn_moves {
+        let squares = &pdn_move.squares;
+
+        let legal = replay.as_ref().and_then(|(board, player)| {
+            board.legal_moves(player).into_iter().find(|legal| {
+                legal.path == *squares
+                    || (squares.len() == 2
+                        && legal.is_capture()
+                        && legal.initial() == squares[0]
+                        && legal.destination() == squares[1])
+            })
+        });
+
+        let path = match (&mut replay, legal) {
+            (Some((board, player)), Some(legal)) => {
+                board.apply(&legal);
+
+                *player = player.opponent();
+
+                legal.path
+            }
+            _ => {
+                replay = None;
+
+                squares.clone()
+            }
+        };
+
+        for hop in path.windows(2) {
+            moves.push(Move {
+                initial: hop[0],
+                destination: hop[1],
+                line: pdn_move.line,
+                src: pdn_move.src.clone(),
+            });
+        }
+    }
+
+    moves
+}
+
+/// Drops a leading move number such as `12.` or `12...`
+fn strip_move_number(word: &str) -> &str {
+    match word.rfind('.') {
+        Some(index) if word[..index].trim_end_matches('.').parse::<u32>().is_ok() => {
+            &word[index + 1..]
+        }
+        _ => word,
+    }
+}
+
+/// The squares of a move like `11-15` or `15x24x31`
+pub(crate) fn parse_squares(word: &str, rules: &dyn RuleSet) -> Option<Vec<Position>> {
+    let word = word.trim_end_matches(['!', '?']);
+
+    let squares: Vec<&str> = if word.contains('x') {
+        word.split('x').collect()
+    } else {
+        word.split('-').collect()
+    };
+
+    if squares.len() < 2 || (!word.contains('x') && squares.len() != 2) {
+        return None;
+    }
+
+    squares
+        .iter()
+        .map(|square| rules.square_position(square.parse().ok()?))
+        .collect()
+}
+
+struct Scanner {
+    chars: Vec<char>,
+    index: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Scanner {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+
+        self.index += 1;
+
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(c)
+    }
+
+    fn skip_until(&mut self, end: char) -> bool {
+        while let Some(c) = self.next() {
+            if c == end {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn error(&self, line: usize, column: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError { line, column, kind }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut scanner = Scanner {
+        chars: input.chars().collect(),
+        index: 0,
+        line: 1,
+        column: 1,
+    };
+
+    let mut tokens = vec![];
+
+    while let Some(c) = scanner.peek() {
+        let (line, column) = (scanner.line, scanner.column);
+
+        match c {
+            _ if c.is_whitespace() => {
+                scanner.next();
+            }
+            '{' => {
+                if !scanner.skip_until('}') {
+                    return Err(scanner.error(line, column, ParseErrorKind::UnterminatedComment));
+                }
+            }
+            ';' => {
+                scanner.skip_until('\n');
+            }
+            '%' if column == 1 => {
+                scanner.skip_until('\n');
+            }
+            '(' => {
+                skip_variation(&mut scanner).ok_or_else(|| {
+                    scanner.error(line, column, ParseErrorKind::UnterminatedComment)
+                })?;
+            }
+            '[' => {
+                scanner.next();
+
+                let tag = read_tag(&mut scanner)
+                    .ok_or_else(|| scanner.error(line, column, ParseErrorKind::InvalidTag))?;
+
+                tokens.push(Token::Tag(tag.0, tag.1, line, column));
+            }
+            _ => {
+                let mut word = String::new();
+
+                while let Some(c) = scanner.peek() {
+                    if c.is_whitespace() || "{;([".contains(c) {
+                        break;
+                    }
+
+                    word.push(c);
+                    scanner.next();
+                }
+
+                tokens.push(Token::Word(word, line, column));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Skips a possibly nested `( ... )` variation, `None` if it never closes
+fn skip_variation(scanner: &mut Scanner) -> Option<()> {
+    let mut depth = 0;
+
+    while let Some(c) = scanner.next() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return Some(());
+                }
+            }
+            '{' if !scanner.skip_until('}') => return None,
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Reads the rest of a `[Name "Value"]` tag pair
+fn read_tag(scanner: &mut Scanner) -> Option<(String, String)> {
+    let mut name = String::new();
+
+    while let Some(c) = scanner.peek() {
+        if c.is_whitespace() || c == '"' {
+            break;
+        }
+
+        name.push(c);
+        scanner.next();
+    }
+
+    while scanner.peek()?.is_whitespace() {
+        scanner.next();
+    }
+
+    if name.is_empty() || scanner.next()? != '"' {
+        return None;
+    }
+
+    let mut value = String::new();
+
+    loop {
+        match scanner.next()? {
+            '\\' => value.push(scanner.next()?),
+            '"' => break,
+            c => value.push(c),
+        }
+    }
+
+    while let Some(c) = scanner.next() {
+        match c {
+            ']' => return Some((name, value)),
+            _ if c.is_whitespace() => (),
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn move_text() {
+        let games = parse_pdn(
+            "[Event \"Test\"]\n\
+             [FEN \"B:W23,K32:B18\"]\n\
+             1. 18x27 {forced} 32x23 1-0\n\
+             [Event \"Second\"]\n\
+             1. 11-15 (1. 9-13) 23-19 *",
+        )
+        .unwrap();
+
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].tag("Event"), Some("Test"));
+        assert_eq!(games[0].result.as_deref(), Some("1-0"));
+        assert_eq!(games[0].record.moves.len(), 2);
+        assert_eq!(games[0].record.moves[1].initial, Position { x: 1, y: 0 });
+        assert_eq!(games[0].record.moves[1].src, "32x23");
+        assert_eq!(games[1].record.moves.len(), 2);
+        assert_eq!(games[1].record.moves[0].line, 5);
+    }
+
+    #[test]
+    fn short_capture_is_expanded() {
+        let games = parse_pdn("[FEN \"W:W27:B23,15\"]\n1. 27x11 1-0").unwrap();
+        let moves = &games[0].record.moves;
+
+        assert_eq!(moves.len(), 2);
+        assert_eq!(
+            moves[0].destination,
+            rules::american().square_position(18).unwrap()
+        );
+        assert_eq!(
+            moves[1].destination,
+            rules::american().square_position(11).unwrap()
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{player::Player, rules::DIAGONALS};
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Kind {
+    Man,
+    King,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub struct Piece {
+    pub player: Player,
+    pub kind: Kind,
+}
+
+impl Piece {
+    pub fn man(player: Player) -> Self {
+        Self {
+            player,
+            kind: Kind::Man,
+        }
+    }
+
+    pub fn king(player: Player) -> Self {
+        Self {
+            player,
+            kind: Kind::King,
+        }
+    }
+
+    pub fn is_king(&self) -> bool {
+        match self.kind {
+            Kind::Man => false,
+            Kind::King => true,
+        }
+    }
+
+    /// The diagonal directions this piece is allowed to move and jump in.
+    /// Men only go forward, kings go both ways.
+    pub fn directions(&self) -> Vec<(i32, i32)> {
+        match self.kind {
+            Kind::Man => {
+                let forward = self.player.forward();
+
+                vec![(1, forward), (-1, forward)]
+            }
+            Kind::King => DIAGONALS.to_vec(),
+        }
+    }
+
+    pub fn crowned(&self) -> Self {
+        Self::king(self.player)
+    }
+}
@@ -1,11 +1,52 @@
 use std::fmt;
 
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum Player {
     Red,
     White,
 }
 
+/// What each side is called in answers and debug output, e.g. black and
+/// white for data that doesn't use red
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Names {
+    pub red: String,
+    pub white: String,
+}
+
+impl Default for Names {
+    fn default() -> Self {
+        Self::new("red", "white")
+    }
+}
+
+impl Names {
+    pub fn new(red: &str, white: &str) -> Self {
+        Self {
+            red: red.to_string(),
+            white: white.to_string(),
+        }
+    }
+
+    pub fn name(&self, player: Player) -> &str {
+        match player {
+            Player::Red => &self.red,
+            Player::White => &self.white,
+        }
+    }
+
+    /// The player called `name`, either by these names or by red and white
+    pub fn player(&self, name: &str) -> Option<Player> {
+        if name == self.red || name == "red" {
+            Some(Player::Red)
+        } else if name == self.white || name == "white" {
+            Some(Player::White)
+        } else {
+            None
+        }
+    }
+}
+
 impl Player {
     pub fn is_red(&self) -> bool {
         match self {
@@ -20,6 +61,21 @@ impl Player {
             Self::White => true,
         }
     }
+
+    pub fn opponent(&self) -> Self {
+        match self {
+            Self::Red => Self::White,
+            Self::White => Self::Red,
+        }
+    }
+
+    /// The direction along the y axis a man of this player moves in
+    pub fn forward(&self) -> i32 {
+        match self {
+            Self::Red => -1,
+            Self::White => 1,
+        }
+    }
 }
 
 impl fmt::Display for Player {
@@ -0,0 +1,84 @@
+use std::fmt;
+
+use crate::r#move::{Notation, Position};
+
+/// Why a move was rejected
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Reason {
+    /// One of the squares is not on the board
+    OutOfBounds,
+    /// There is no piece on the starting square
+    EmptySquare,
+    /// The piece on the starting square belongs to the other player
+    OpponentsPiece,
+    /// The move is not a one or two square diagonal
+    NotDiagonal,
+    /// The move is not along a row or column, for rules where pieces only
+    /// move orthogonally
+    NotOrthogonal,
+    /// A man tried to move towards its own side
+    Backwards,
+    /// A man tried to move further than it can, a move only a flying king
+    /// makes
+    TooFar,
+    /// The destination already holds a piece
+    Occupied,
+    /// A jump over an empty square
+    NothingToJump,
+    /// A jump over one of the player's own pieces
+    JumpsOwnPiece,
+    /// A jump over a piece the rules don't let this one capture, e.g. a man
+    /// jumping a king in Italian draughts
+    CantCapture,
+    /// A flying king jumped over more than one piece at once
+    JumpsSeveralPieces,
+    /// A step was made while the piece on this square could jump
+    CompulsoryJump(Position),
+    /// A multi-jump was abandoned while the piece on this square could keep jumping
+    UnfinishedJump(Position),
+    /// The player moved again after their turn was over
+    TurnOver,
+    /// A king moved without capturing once the rules' limit on king moves
+    /// in a row was reached
+    KingMoveLimit,
+    /// The capture isn't one the rules allow, e.g. because the capture from
+    /// this square takes more pieces
+    CapturePriority(Position),
+}
+
+impl Reason {
+    /// Describes the reason with any square written in the given notation
+    pub fn notate(&self, notation: Notation) -> String {
+        match self {
+            Self::OutOfBounds => "off the board".to_string(),
+            Self::EmptySquare => "no piece to move".to_string(),
+            Self::OpponentsPiece => "moves the opponent's piece".to_string(),
+            Self::NotDiagonal => "not a diagonal move".to_string(),
+            Self::NotOrthogonal => "not an orthogonal move".to_string(),
+            Self::Backwards => "men can't move backwards".to_string(),
+            Self::TooFar => "men only move one square".to_string(),
+            Self::Occupied => "destination is occupied".to_string(),
+            Self::NothingToJump => "nothing to jump".to_string(),
+            Self::JumpsOwnPiece => "jumps its own piece".to_string(),
+            Self::CantCapture => "can't capture that piece".to_string(),
+            Self::JumpsSeveralPieces => "jumps more than one piece".to_string(),
+            Self::CompulsoryJump(pos) => {
+                format!("compulsory jump available from {}", pos.notate(notation))
+            }
+            Self::UnfinishedJump(pos) => {
+                format!("jump must continue from {}", pos.notate(notation))
+            }
+            Self::TurnOver => "the turn is over".to_string(),
+            Self::KingMoveLimit => "too many king moves in a row, a man has to move".to_string(),
+            Self::CapturePriority(pos) => {
+                format!("the capture from {} takes priority", pos.notate(notation))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.notate(Notation::default()))
+    }
+}
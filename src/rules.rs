@@ -0,0 +1,700 @@
+use std::{fmt, sync::Arc};
+
+use crate::{
+    board::Board,
+    piece::Piece,
+    player::Player,
+    r#move::{LegalMove, Position},
+};
+
+mod american;
+mod antidraughts;
+mod canadian;
+mod custom;
+mod frisian;
+mod international;
+mod italian;
+mod resized;
+mod russian;
+mod turkish;
+
+pub use self::{
+    american::American,
+    antidraughts::Antidraughts,
+    canadian::Canadian,
+    custom::{Custom, RulesFileError},
+    frisian::Frisian,
+    international::International,
+    italian::Italian,
+    resized::Resized,
+    russian::Russian,
+    turkish::Turkish,
+};
+
+/// What becomes of a man that reaches the far row in the middle of a capture
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Crowning {
+    /// It is crowned and the capture ends there
+    EndsCapture,
+    /// It is crowned and carries on capturing as a king
+    ContinuesAsKing,
+    /// It carries on capturing as a man and is only crowned if the capture
+    /// finishes on the far row
+    ContinuesAsMan,
+}
+
+/// The rules of a draughts variant, everything `Board` and `Game` need to
+/// know about how the pieces are set up, move, capture and get crowned, and
+/// who has won once the game is over
+pub trait RuleSet: fmt::Debug + Send + Sync {
+    /// The name the rules are selected by, e.g. with `--rules`
+    fn name(&self) -> &str;
+
+    /// The width and height of the board in squares
+    fn size(&self) -> (i32, i32) {
+        (8, 8)
+    }
+
+    /// Whether pieces can stand on `position`, the dark squares unless the
+    /// rules say otherwise
+    fn is_playable(&self, position: Position) -> bool {
+        (position.x + position.y) % 2 == 1
+    }
+
+    /// The position of a square in the standard numbering, which counts the
+    /// playable squares row by row from red's side, each row from high `x`
+    /// to low `x`
+    fn square_position(&self, square: u32) -> Option<Position> {
+        let (width, height) = self.size();
+
+        (0..height)
+            .rev()
+            .flat_map(|y| (0..width).rev().map(move |x| Position { x, y }))
+            .filter(|position| self.is_playable(*position))
+            .nth(square.checked_sub(1)? as usize)
+    }
+
+    /// The number of `position` in the standard numbering, see
+    /// `square_position`
+    fn square_number(&self, position: Position) -> Option<u32> {
+        let (width, height) = self.size();
+
+        (0..height)
+            .rev()
+            .flat_map(|y| (0..width).rev().map(move |x| Position { x, y }))
+            .filter(|position| self.is_playable(*position))
+            .position(|other| other == position)
+            .map(|index| index as u32 + 1)
+    }
+
+    /// The number of the variant in the PDN `GameType` tag
+    fn game_type(&self) -> Option<u32> {
+        None
+    }
+
+    /// Every piece on the board at the start of a game
+    fn starting_pieces(&self) -> Vec<(Position, Piece)>;
+
+    /// Every piece at the start of a game when the rules are played on a
+    /// board of another size, see `resize`. Men fill every playable square
+    /// of each side's half of the board, leaving the two middle rows empty.
+    fn starting_pieces_on(&self, size: (i32, i32)) -> Vec<(Position, Piece)> {
+        men_in_rows(self, size, (size.1 - 2) / 2)
+    }
+
+    /// The side that makes the first move of a game
+    fn first_player(&self) -> Player;
+
+    /// The side PDN games start with when they have no `FEN` tag
+    fn pdn_first_player(&self) -> Player {
+        self.first_player()
+    }
+
+    /// The directions `piece` can step in without capturing
+    fn move_directions(&self, piece: &Piece) -> Vec<(i32, i32)>;
+
+    /// The directions `piece` can capture in
+    fn capture_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        self.move_directions(piece)
+    }
+
+    /// Whether kings move and capture over any number of empty squares
+    fn flying_kings(&self) -> bool {
+        false
+    }
+
+    /// Whether `piece` moves over any number of empty squares
+    fn is_flying(&self, piece: &Piece) -> bool {
+        piece.is_king() && self.flying_kings()
+    }
+
+    /// Whether `piece` is allowed to capture `target`
+    fn can_capture(&self, _piece: &Piece, _target: &Piece) -> bool {
+        true
+    }
+
+    /// Whether captured pieces come off the board as soon as they are jumped
+    /// instead of once the whole capture is over. A capture may then cross
+    /// the same square twice, but it can't turn straight back.
+    fn removes_captured_immediately(&self) -> bool {
+        false
+    }
+
+    /// How many moves without a capture a player's kings may make in a row
+    /// while the player still has men, `None` for no limit
+    fn king_move_limit(&self) -> Option<u32> {
+        None
+    }
+
+    /// Whether a player who is able to capture has to
+    fn compulsory_capture(&self) -> bool {
+        true
+    }
+
+    /// Narrows down every capture available to a player to the ones they
+    /// are allowed to choose from, e.g. the ones taking the most pieces
+    fn choose_captures(&self, _board: &Board, captures: Vec<LegalMove>) -> Vec<LegalMove> {
+        captures
+    }
+
+    /// Whether `piece` gets crowned when it finishes a move on `position`,
+    /// by default a man reaching the opponent's back row
+    fn promotes(&self, piece: &Piece, position: Position) -> bool {
+        let far_row = match piece.player {
+            Player::Red => 0,
+            Player::White => self.size().1 - 1,
+        };
+
+        !piece.is_king() && position.y == far_row
+    }
+
+    /// What a man crowned in the middle of a capture does
+    fn crowning(&self) -> Crowning {
+        Crowning::EndsCapture
+    }
+
+    /// Who won a finished game, `None` for a tie
+    fn winner(&self, board: &Board) -> Option<Player> {
+        let red_score = board.red_score();
+        let white_score = board.white_score();
+
+        if white_score == red_score {
+            None
+        } else if white_score > red_score {
+            Some(Player::White)
+        } else {
+            Some(Player::Red)
+        }
+    }
+}
+
+/// A set of rules shared by every board of a game
+pub type Rules = Arc<dyn RuleSet>;
+
+/// The names accepted by `from_name`
+pub const NAMES: [&str; 8] = [
+    "american",
+    "antidraughts",
+    "canadian",
+    "frisian",
+    "international",
+    "italian",
+    "russian",
+    "turkish",
+];
+
+/// Looks up one of the built in rule sets
+pub fn from_name(name: &str) -> Option<Rules> {
+    match name {
+        "american" => Some(Arc::new(American)),
+        "antidraughts" => Some(Arc::new(Antidraughts)),
+        "canadian" => Some(Arc::new(Canadian)),
+        "frisian" => Some(Arc::new(Frisian)),
+        "international" => Some(Arc::new(International)),
+        "italian" => Some(Arc::new(Italian)),
+        "russian" => Some(Arc::new(Russian)),
+        "turkish" => Some(Arc::new(Turkish)),
+        _ => None,
+    }
+}
+
+/// American checkers, also known as English draughts, the rules used when
+/// nothing else is asked for
+pub fn american() -> Rules {
+    Arc::new(American)
+}
+
+/// `rules` played on a `width` by `height` board, `None` when the board is
+/// too small to keep the two sides' starting pieces apart, too wide to name
+/// its files a to z or has more playable squares than a `Board` can hold
+pub fn resize(rules: Rules, (width, height): (i32, i32)) -> Option<Rules> {
+    if height < 4 {
+        return None;
+    }
+
+    let resized = Resized::new(rules, (width, height));
+
+    if !fits_on_board(&resized) {
+        return None;
+    }
+
+    let pieces = resized.starting_pieces();
+
+    let overlapping = pieces
+        .iter()
+        .enumerate()
+        .any(|(index, (position, _))| pieces[..index].iter().any(|(other, _)| other == position));
+
+    if overlapping {
+        return None;
+    }
+
+    Some(Arc::new(resized))
+}
+
+/// Reads a variant described in a TOML file, see `Custom`
+pub fn from_toml(src: &str) -> Result<Rules, RulesFileError> {
+    Ok(Arc::new(Custom::from_toml(src)?))
+}
+
+/// Whether a `Board` can be laid out for `rules`, no more than 26 files
+/// wide so they can be named a to z and with at most 128 playable squares
+fn fits_on_board(rules: &dyn RuleSet) -> bool {
+    let (width, height) = rules.size();
+
+    if !(2..=26).contains(&width) || !(2..=128).contains(&height) {
+        return false;
+    }
+
+    let playable = (0..height)
+        .flat_map(|y| (0..width).map(move |x| Position { x, y }))
+        .filter(|position| rules.is_playable(*position))
+        .count();
+
+    playable <= 128
+}
+
+/// The four diagonal directions, for kings and for men that capture
+/// backwards as well as forwards
+pub(crate) const DIAGONALS: [(i32, i32); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
+
+/// Men on every playable square of the first `rows` rows of each side
+fn starting_men(rules: &dyn RuleSet, rows: i32) -> Vec<(Position, Piece)> {
+    men_in_rows(rules, rules.size(), rows)
+}
+
+/// Same as `starting_men` on a board of the given width and height
+fn men_in_rows<R: RuleSet + ?Sized>(
+    rules: &R,
+    (width, height): (i32, i32),
+    rows: i32,
+) -> Vec<(Position, Piece)> {
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| Position { x, y }))
+        .filter(|position| rules.is_playable(*position))
+        .filter_map(|position| {
+            if position.y < rows {
+                Some((position, Piece::man(Player::White)))
+            } else if position.y >= height - rows {
+                Some((position, Piece::man(Player::Red)))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// The captures taking the most pieces
+fn most_captured(captures: Vec<LegalMove>) -> Vec<LegalMove> {
+    let most = captures
+        .iter()
+        .map(|capture| capture.captured.len())
+        .max()
+        .unwrap_or_default();
+
+    captures
+        .into_iter()
+        .filter(|capture| capture.captured.len() == most)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{r#move::Move, reason::Reason};
+
+    /// American rules where jumping is optional
+    #[derive(Debug)]
+    struct OptionalCapture;
+
+    impl RuleSet for OptionalCapture {
+        fn name(&self) -> &str {
+            "optional-capture"
+        }
+
+        fn starting_pieces(&self) -> Vec<(Position, Piece)> {
+            American.starting_pieces()
+        }
+
+        fn first_player(&self) -> Player {
+            American.first_player()
+        }
+
+        fn move_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+            American.move_directions(piece)
+        }
+
+        fn compulsory_capture(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn pluggable_rules() {
+        assert_eq!(Board::with_rules(american()), Board::new());
+        assert!(from_name("checkers").is_none());
+
+        let pieces = vec![
+            (Position { x: 1, y: 0 }, Piece::man(Player::White)),
+            (Position { x: 2, y: 1 }, Piece::man(Player::Red)),
+        ];
+
+        let american = Board::from_pieces(pieces.clone()).unwrap();
+        let optional = Board::from_pieces_with_rules(Arc::new(OptionalCapture), pieces).unwrap();
+
+        assert_eq!(american.legal_moves(&Player::White).len(), 1);
+        assert_eq!(optional.legal_moves(&Player::White).len(), 2);
+    }
+
+    #[test]
+    fn international_perft() {
+        let board = Board::with_rules(from_name("international").unwrap());
+
+        let nodes: Vec<u64> = (1..=5)
+            .map(|depth| board.perft(&Player::White, depth))
+            .collect();
+
+        assert_eq!(nodes, vec![9, 81, 658, 4265, 27117]);
+        assert_eq!(
+            board.rules().square_position(46),
+            Some(Position { x: 9, y: 0 })
+        );
+    }
+
+    #[test]
+    fn canadian_perft() {
+        let board = Board::with_rules(from_name("canadian").unwrap());
+
+        let nodes: Vec<u64> = (1..=4)
+            .map(|depth| board.perft(&Player::White, depth))
+            .collect();
+
+        assert_eq!(board.count(&Player::Red), 30);
+        assert_eq!(nodes, vec![11, 121, 1222, 10053]);
+    }
+
+    #[test]
+    fn resized_boards() {
+        let small = Board::with_rules(resize(american(), (6, 6)).unwrap());
+        let wide = Board::with_rules(resize(american(), (10, 8)).unwrap());
+
+        assert_eq!(small.count(&Player::White), 6);
+        assert_eq!(small.legal_moves(&Player::White).len(), 5);
+        assert_eq!(wide.count(&Player::Red), 15);
+        assert_eq!(
+            wide.rules().square_position(40),
+            Some(Position { x: 1, y: 0 })
+        );
+
+        assert!(resize(american(), (30, 8)).is_none());
+        assert!(resize(from_name("turkish").unwrap(), (12, 12)).is_none());
+        assert!(resize(from_name("turkish").unwrap(), (8, 5)).is_none());
+    }
+
+    #[test]
+    fn resized_turkish() {
+        let turkish = from_name("turkish").unwrap();
+
+        let same = resize(turkish.clone(), (8, 8)).unwrap();
+
+        assert_eq!(same.starting_pieces(), turkish.starting_pieces());
+
+        let board = Board::with_rules(resize(turkish, (10, 10)).unwrap());
+
+        assert_eq!(board.count(&Player::White), 20);
+        assert_eq!(board.get(0, 0), None);
+        assert_eq!(board.get(0, 2), Some(Piece::man(Player::White)));
+        assert_eq!(board.get(9, 7), Some(Piece::man(Player::Red)));
+    }
+
+    #[test]
+    fn frisian_captures_and_king_moves() {
+        let board = Board::from_pieces_with_rules(
+            Arc::new(Frisian),
+            vec![
+                (Position { x: 4, y: 1 }, Piece::man(Player::White)),
+                (Position { x: 4, y: 3 }, Piece::man(Player::Red)),
+                (Position { x: 2, y: 1 }, Piece::king(Player::Red)),
+            ],
+        )
+        .unwrap();
+
+        let moves = board.legal_moves(&Player::White);
+
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].destination(), Position { x: 0, y: 1 });
+
+        let mut board = Board::from_pieces_with_rules(
+            Arc::new(Frisian),
+            vec![
+                (Position { x: 0, y: 1 }, Piece::king(Player::White)),
+                (Position { x: 9, y: 0 }, Piece::man(Player::White)),
+                (Position { x: 9, y: 8 }, Piece::man(Player::Red)),
+            ],
+        )
+        .unwrap();
+
+        for path in [[(0, 1), (1, 2)], [(1, 2), (0, 3)], [(0, 3), (1, 4)]] {
+            board.apply(&LegalMove {
+                path: path.iter().map(|&(x, y)| Position { x, y }).collect(),
+                captured: vec![],
+            });
+        }
+
+        let moves = board.legal_moves(&Player::White);
+
+        assert!(moves
+            .iter()
+            .all(|mov| mov.initial() == Position { x: 9, y: 0 }));
+
+        let mov = Move {
+            initial: Position { x: 1, y: 4 },
+            destination: Position { x: 2, y: 5 },
+            line: 1,
+            src: String::new(),
+        };
+
+        assert_eq!(
+            board.make_move(&Player::White, &mov),
+            Err(Reason::KingMoveLimit)
+        );
+    }
+
+    #[test]
+    fn rules_file() {
+        let rules = from_toml(
+            r#"
+            size = [6, 6]
+            men-capture-backwards = true
+            win-condition = "fewest-pieces"
+
+            [start]
+            white = ["1,2", "K5,0"]
+            red = ["2,1"]
+            "#,
+        )
+        .unwrap();
+
+        let board = Board::with_rules(rules);
+
+        assert_eq!(board.size(), (6, 6));
+        assert_eq!(board.legal_moves(&Player::Red).len(), 1);
+        assert_eq!(board.rules().winner(&board), Some(Player::Red));
+
+        let err = from_toml("size = [8, 8]\n[start]\nwhite = [\"0,0\"]").unwrap_err();
+
+        assert_eq!(err.to_string(), "`0,0` is not a square");
+        assert!(from_toml("sise = [8, 8]").is_err());
+
+        let american_like = from_toml("").unwrap();
+
+        assert_eq!(american_like.first_player(), Player::White);
+        assert_eq!(american_like.pdn_first_player(), Player::Red);
+
+        for (src, message) in [
+            (
+                "[start]\nrows = 4",
+                "4 rows of men a side don't fit on the board",
+            ),
+            (
+                "[start]\nrows = -3",
+                "-3 rows of men a side don't fit on the board",
+            ),
+            (
+                "[start]\nrows = 2\nred = [\"c7\"]",
+                "`rows` can't be given along with squares",
+            ),
+        ] {
+            assert_eq!(from_toml(src).unwrap_err().to_string(), message);
+        }
+    }
+
+    #[test]
+    fn russian_crowning_mid_capture() {
+        let pieces = vec![
+            (Position { x: 2, y: 5 }, Piece::man(Player::White)),
+            (Position { x: 3, y: 6 }, Piece::man(Player::Red)),
+            (Position { x: 5, y: 6 }, Piece::man(Player::Red)),
+        ];
+
+        let american = Board::from_pieces(pieces.clone()).unwrap();
+        let mut russian = Board::from_pieces_with_rules(Arc::new(Russian), pieces).unwrap();
+
+        assert_eq!(american.legal_moves(&Player::White).len(), 1);
+
+        let moves = russian.legal_moves(&Player::White);
+        let destinations: Vec<Position> = moves.iter().map(LegalMove::destination).collect();
+
+        assert_eq!(
+            destinations,
+            vec![Position { x: 6, y: 5 }, Position { x: 7, y: 4 }]
+        );
+
+        russian.apply(&moves[1]);
+
+        assert_eq!(russian.get(7, 4), Some(Piece::king(Player::White)));
+    }
+
+    #[test]
+    fn men_dont_fly() {
+        let mut board = Board::from_pieces_with_rules(
+            Arc::new(Russian),
+            vec![
+                (Position { x: 1, y: 2 }, Piece::man(Player::White)),
+                (Position { x: 6, y: 7 }, Piece::man(Player::Red)),
+            ],
+        )
+        .unwrap();
+
+        let forwards = Move {
+            initial: Position { x: 1, y: 2 },
+            destination: Position { x: 4, y: 5 },
+            line: 1,
+            src: String::new(),
+        };
+
+        assert_eq!(
+            board.make_move(&Player::White, &forwards),
+            Err(Reason::TooFar)
+        );
+
+        let backwards = Move {
+            destination: Position { x: 0, y: 1 },
+            ..forwards
+        };
+
+        assert_eq!(
+            board.make_move(&Player::White, &backwards),
+            Err(Reason::Backwards)
+        );
+    }
+
+    #[test]
+    fn russian_king_lands_where_it_can_keep_capturing() {
+        let board = Board::from_pieces_with_rules(
+            Arc::new(Russian),
+            vec![
+                (Position { x: 1, y: 0 }, Piece::king(Player::White)),
+                (Position { x: 3, y: 2 }, Piece::man(Player::Red)),
+                (Position { x: 5, y: 2 }, Piece::man(Player::Red)),
+                (Position { x: 6, y: 7 }, Piece::man(Player::Red)),
+            ],
+        )
+        .unwrap();
+
+        let moves = board.legal_moves(&Player::White);
+        let paths: Vec<&[Position]> = moves.iter().map(|mov| mov.path.as_slice()).collect();
+
+        let start = Position { x: 1, y: 0 };
+        let landing = Position { x: 4, y: 3 };
+
+        assert_eq!(
+            paths,
+            vec![
+                [start, landing, Position { x: 6, y: 1 }],
+                [start, landing, Position { x: 7, y: 0 }],
+            ]
+        );
+    }
+
+    #[test]
+    fn antidraughts_blocked_player_wins() {
+        let white = [(1, 0), (3, 0), (5, 0), (7, 0)];
+        let red = [(0, 1), (2, 1), (4, 1), (6, 1), (1, 2)];
+
+        let pieces: Vec<(Position, Piece)> = white
+            .iter()
+            .map(|&(x, y)| (Position { x, y }, Piece::man(Player::White)))
+            .chain(
+                red.iter()
+                    .map(|&(x, y)| (Position { x, y }, Piece::man(Player::Red))),
+            )
+            .collect();
+
+        let board = Board::from_pieces_with_rules(Arc::new(Antidraughts), pieces).unwrap();
+
+        assert!(!board.has_legal_moves(&Player::Red));
+        assert_eq!(Antidraughts.winner(&board), Some(Player::Red));
+    }
+
+    #[test]
+    fn italian_capture_priority() {
+        let mut board = Board::from_pieces_with_rules(
+            Arc::new(Italian),
+            vec![
+                (Position { x: 2, y: 2 }, Piece::man(Player::White)),
+                (Position { x: 6, y: 0 }, Piece::king(Player::White)),
+                (Position { x: 3, y: 3 }, Piece::man(Player::Red)),
+                (Position { x: 5, y: 1 }, Piece::man(Player::Red)),
+                (Position { x: 1, y: 3 }, Piece::king(Player::Red)),
+            ],
+        )
+        .unwrap();
+
+        let moves = board.legal_moves(&Player::White);
+
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].initial(), Position { x: 6, y: 0 });
+
+        let mov = Move {
+            initial: Position { x: 2, y: 2 },
+            destination: Position { x: 0, y: 4 },
+            line: 1,
+            src: String::new(),
+        };
+
+        assert_eq!(
+            board.make_move(&Player::White, &mov),
+            Err(Reason::CantCapture)
+        );
+    }
+
+    #[test]
+    fn turkish_removes_captured_immediately() {
+        let board = Board::from_pieces_with_rules(
+            Arc::new(Turkish),
+            vec![
+                (Position { x: 2, y: 0 }, Piece::king(Player::White)),
+                (Position { x: 2, y: 2 }, Piece::man(Player::Red)),
+                (Position { x: 4, y: 5 }, Piece::man(Player::Red)),
+                (Position { x: 6, y: 3 }, Piece::man(Player::Red)),
+                (Position { x: 1, y: 2 }, Piece::man(Player::Red)),
+            ],
+        )
+        .unwrap();
+
+        let moves = board.legal_moves(&Player::White);
+
+        assert_eq!(moves.len(), 1);
+        assert_eq!(
+            moves[0].path,
+            vec![
+                Position { x: 2, y: 0 },
+                Position { x: 2, y: 5 },
+                Position { x: 6, y: 5 },
+                Position { x: 6, y: 2 },
+                Position { x: 0, y: 2 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{piece::Piece, player::Player, r#move::Position, rules::RuleSet};
+
+const INITIAL_WHITE_POSITIONS: [(i32, i32); 12] = [
+    (1, 0),
+    (3, 0),
+    (5, 0),
+    (7, 0),
+    (0, 1),
+    (2, 1),
+    (4, 1),
+    (6, 1),
+    (1, 2),
+    (3, 2),
+    (5, 2),
+    (7, 2),
+];
+
+const INITIAL_RED_POSITIONS: [(i32, i32); 12] = [
+    (0, 5),
+    (2, 5),
+    (4, 5),
+    (6, 5),
+    (1, 6),
+    (3, 6),
+    (5, 6),
+    (7, 6),
+    (0, 7),
+    (2, 7),
+    (4, 7),
+    (6, 7),
+];
+
+/// American checkers. Men step and jump forward only, kings both ways, one
+/// square at a time. A man crowned while jumping ends the turn.
+#[derive(Debug)]
+pub struct American;
+
+impl RuleSet for American {
+    fn name(&self) -> &str {
+        "american"
+    }
+
+    fn game_type(&self) -> Option<u32> {
+        Some(21)
+    }
+
+    fn starting_pieces(&self) -> Vec<(Position, Piece)> {
+        let men = |positions: &'static [(i32, i32)], player: Player| {
+            positions
+                .iter()
+                .map(move |&(x, y)| (Position { x, y }, Piece::man(player)))
+        };
+
+        men(&INITIAL_WHITE_POSITIONS, Player::White)
+            .chain(men(&INITIAL_RED_POSITIONS, Player::Red))
+            .collect()
+    }
+
+    /// Input files have always started with white
+    fn first_player(&self) -> Player {
+        Player::White
+    }
+
+    /// Black moves first in PDN, which is red here
+    fn pdn_first_player(&self) -> Player {
+        Player::Red
+    }
+
+    fn move_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        piece.directions()
+    }
+}
@@ -0,0 +1,48 @@
+use crate::{
+    board::Board,
+    piece::Piece,
+    player::Player,
+    r#move::Position,
+    rules::{American, RuleSet},
+};
+
+/// Giveaway checkers, American checkers played to lose. Captures are still
+/// compulsory, but the player who can't move, having lost every piece or
+/// being blocked, is the one who wins.
+#[derive(Debug)]
+pub struct Antidraughts;
+
+impl RuleSet for Antidraughts {
+    fn name(&self) -> &str {
+        "antidraughts"
+    }
+
+    fn starting_pieces(&self) -> Vec<(Position, Piece)> {
+        American.starting_pieces()
+    }
+
+    fn first_player(&self) -> Player {
+        American.first_player()
+    }
+
+    fn pdn_first_player(&self) -> Player {
+        American.pdn_first_player()
+    }
+
+    fn move_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        American.move_directions(piece)
+    }
+
+    /// The side left without a move, or the one with fewer pieces when
+    /// neither or both can move
+    fn winner(&self, board: &Board) -> Option<Player> {
+        let red_moves = board.has_legal_moves(&Player::Red);
+        let white_moves = board.has_legal_moves(&Player::White);
+
+        match (red_moves, white_moves) {
+            (false, true) => Some(Player::Red),
+            (true, false) => Some(Player::White),
+            _ => American.winner(board).map(|player| player.opponent()),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{
+    board::Board,
+    piece::Piece,
+    player::Player,
+    r#move::{LegalMove, Position},
+    rules::{starting_men, Crowning, International, RuleSet},
+};
+
+/// Canadian checkers, international draughts on a 12x12 board with 30 men
+/// a side
+#[derive(Debug)]
+pub struct Canadian;
+
+impl RuleSet for Canadian {
+    fn name(&self) -> &str {
+        "canadian"
+    }
+
+    fn size(&self) -> (i32, i32) {
+        (12, 12)
+    }
+
+    fn game_type(&self) -> Option<u32> {
+        Some(27)
+    }
+
+    fn starting_pieces(&self) -> Vec<(Position, Piece)> {
+        starting_men(self, 5)
+    }
+
+    fn first_player(&self) -> Player {
+        International.first_player()
+    }
+
+    fn move_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        International.move_directions(piece)
+    }
+
+    fn capture_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        International.capture_directions(piece)
+    }
+
+    fn flying_kings(&self) -> bool {
+        International.flying_kings()
+    }
+
+    fn choose_captures(&self, board: &Board, captures: Vec<LegalMove>) -> Vec<LegalMove> {
+        International.choose_captures(board, captures)
+    }
+
+    fn crowning(&self) -> Crowning {
+        International.crowning()
+    }
+}
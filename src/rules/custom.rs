@@ -0,0 +1,276 @@
+use std::{error::Error, fmt};
+
+use serde::Deserialize;
+
+use crate::{
+    board::Board,
+    piece::Piece,
+    player::Player,
+    r#move::{Coordinates, LegalMove, Position},
+    rules::{
+        fits_on_board, most_captured, starting_men, American, Antidraughts, RuleSet, DIAGONALS,
+    },
+    setup::{parse_piece, SetupError},
+};
+
+/// A variant described in a TOML file rather than written in Rust, for
+/// house rules. Every key is optional and anything left out is as in
+/// American checkers:
+///
+/// ```toml
+/// name = "house"
+/// size = [10, 8]
+/// first-player = "red"
+/// men-capture-backwards = true
+/// flying-kings = true
+/// compulsory-capture = true
+/// majority-capture = false
+/// win-condition = "most-pieces" # or "fewest-pieces"
+///
+/// [start]
+/// rows = 3
+/// # or every square, as in a setup line
+/// white = ["1,0", "K3,0"]
+/// red = ["c7", "e7"]
+/// ```
+///
+/// Without `white` and `red` squares each side starts with men on every
+/// playable square of its first `rows` rows, by default all of them but
+/// the two middle rows. `rows` can't be given along with the squares.
+#[derive(Debug)]
+pub struct Custom {
+    name: String,
+    size: (i32, i32),
+    /// `None` to start like American checkers, by PDN convention as well
+    first_player: Option<Player>,
+    starting_pieces: Vec<(Position, Piece)>,
+    men_capture_backwards: bool,
+    flying_kings: bool,
+    compulsory_capture: bool,
+    majority_capture: bool,
+    win_condition: WinCondition,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RulesFileError {
+    Toml(toml::de::Error),
+    InvalidSize(i32, i32),
+    UnknownPlayer(String),
+    InvalidRows(i32),
+    RowsWithSquares,
+    InvalidStart(SetupError),
+}
+
+impl fmt::Display for RulesFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Toml(err) => write!(f, "{}", err),
+            Self::InvalidSize(width, height) => {
+                write!(f, "can't play on a {}x{} board", width, height)
+            }
+            Self::UnknownPlayer(player) => write!(f, "unknown player `{}`", player),
+            Self::InvalidRows(rows) => {
+                write!(f, "{} rows of men a side don't fit on the board", rows)
+            }
+            Self::RowsWithSquares => write!(f, "`rows` can't be given along with squares"),
+            Self::InvalidStart(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for RulesFileError {}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct RulesFile {
+    #[serde(default = "RulesFile::name")]
+    name: String,
+    #[serde(default = "RulesFile::size")]
+    size: (i32, i32),
+    first_player: Option<String>,
+    #[serde(default)]
+    men_capture_backwards: bool,
+    #[serde(default)]
+    flying_kings: bool,
+    #[serde(default = "RulesFile::compulsory_capture")]
+    compulsory_capture: bool,
+    #[serde(default)]
+    majority_capture: bool,
+    #[serde(default)]
+    win_condition: WinCondition,
+    #[serde(default)]
+    start: Start,
+}
+
+impl RulesFile {
+    fn name() -> String {
+        "custom".to_string()
+    }
+
+    fn size() -> (i32, i32) {
+        (8, 8)
+    }
+
+    fn compulsory_capture() -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Start {
+    rows: Option<i32>,
+    #[serde(default)]
+    white: Vec<String>,
+    #[serde(default)]
+    red: Vec<String>,
+}
+
+/// Who wins once nobody can move
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum WinCondition {
+    /// The player with more pieces left
+    #[default]
+    MostPieces,
+    /// The player left without a move, or with fewer pieces, as in
+    /// antidraughts
+    FewestPieces,
+}
+
+impl Custom {
+    pub fn from_toml(src: &str) -> Result<Self, RulesFileError> {
+        let file: RulesFile = toml::from_str(src).map_err(RulesFileError::Toml)?;
+
+        let first_player = match file.first_player.as_deref() {
+            None => None,
+            Some("white") => Some(Player::White),
+            Some("red") => Some(Player::Red),
+            Some(player) => return Err(RulesFileError::UnknownPlayer(player.to_string())),
+        };
+
+        let mut custom = Self {
+            name: file.name,
+            size: file.size,
+            first_player,
+            starting_pieces: vec![],
+            men_capture_backwards: file.men_capture_backwards,
+            flying_kings: file.flying_kings,
+            compulsory_capture: file.compulsory_capture,
+            majority_capture: file.majority_capture,
+            win_condition: file.win_condition,
+        };
+
+        let (width, height) = custom.size;
+
+        if !fits_on_board(&custom) {
+            return Err(RulesFileError::InvalidSize(width, height));
+        }
+
+        let squares = !file.start.white.is_empty() || !file.start.red.is_empty();
+
+        custom.starting_pieces = match file.start.rows {
+            Some(_) if squares => return Err(RulesFileError::RowsWithSquares),
+            Some(rows) if !(1..=(height - 2) / 2).contains(&rows) => {
+                return Err(RulesFileError::InvalidRows(rows))
+            }
+            Some(rows) => starting_men(&custom, rows),
+            None if squares => custom.parse_pieces(&file.start)?,
+            None => starting_men(&custom, (height - 2) / 2),
+        };
+
+        Ok(custom)
+    }
+
+    /// The squares listed for each side in `start`
+    fn parse_pieces(&self, start: &Start) -> Result<Vec<(Position, Piece)>, RulesFileError> {
+        let mut pieces: Vec<(Position, Piece)> = vec![];
+
+        for (player, squares) in [(Player::White, &start.white), (Player::Red, &start.red)] {
+            for square in squares {
+                let (position, piece) =
+                    parse_piece(square, player, self.size, Coordinates::default())
+                        .map_err(RulesFileError::InvalidStart)?;
+
+                let (width, height) = self.size;
+
+                if !(0..width).contains(&position.x)
+                    || !(0..height).contains(&position.y)
+                    || !self.is_playable(position)
+                {
+                    let err = SetupError::InvalidSquare(square.to_string());
+
+                    return Err(RulesFileError::InvalidStart(err));
+                }
+
+                if pieces.iter().any(|(other, _)| *other == position) {
+                    let err = SetupError::DuplicateSquare(position);
+
+                    return Err(RulesFileError::InvalidStart(err));
+                }
+
+                pieces.push((position, piece));
+            }
+        }
+
+        Ok(pieces)
+    }
+}
+
+impl RuleSet for Custom {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    fn starting_pieces(&self) -> Vec<(Position, Piece)> {
+        self.starting_pieces.clone()
+    }
+
+    fn first_player(&self) -> Player {
+        self.first_player.unwrap_or_else(|| American.first_player())
+    }
+
+    fn pdn_first_player(&self) -> Player {
+        self.first_player
+            .unwrap_or_else(|| American.pdn_first_player())
+    }
+
+    fn move_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        piece.directions()
+    }
+
+    fn capture_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        if self.men_capture_backwards {
+            DIAGONALS.to_vec()
+        } else {
+            piece.directions()
+        }
+    }
+
+    fn flying_kings(&self) -> bool {
+        self.flying_kings
+    }
+
+    fn compulsory_capture(&self) -> bool {
+        self.compulsory_capture
+    }
+
+    fn choose_captures(&self, _board: &Board, captures: Vec<LegalMove>) -> Vec<LegalMove> {
+        if self.majority_capture {
+            most_captured(captures)
+        } else {
+            captures
+        }
+    }
+
+    fn winner(&self, board: &Board) -> Option<Player> {
+        match self.win_condition {
+            WinCondition::MostPieces => American.winner(board),
+            WinCondition::FewestPieces => Antidraughts.winner(board),
+        }
+    }
+}
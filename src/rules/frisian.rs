@@ -0,0 +1,88 @@
+use std::cmp::Reverse;
+
+use crate::{
+    board::Board,
+    piece::Piece,
+    player::Player,
+    r#move::{LegalMove, Position},
+    rules::{starting_men, Crowning, RuleSet, DIAGONALS},
+};
+
+/// Frisian draughts, played on a 10x10 board with 20 men a side. Men step
+/// diagonally forwards but capture in every direction, along rows and
+/// columns as well as diagonals, kings fly. The capture taking the most
+/// valuable pieces has to be played, a king being worth a little less than
+/// two men, and on a tie one made with a king. A player with men left may
+/// only move kings three times in a row without capturing.
+#[derive(Debug)]
+pub struct Frisian;
+
+/// Orthogonal steps skip the light squares in between
+const ORTHOGONALS: [(i32, i32); 4] = [(2, 0), (-2, 0), (0, 2), (0, -2)];
+
+impl RuleSet for Frisian {
+    fn name(&self) -> &str {
+        "frisian"
+    }
+
+    fn size(&self) -> (i32, i32) {
+        (10, 10)
+    }
+
+    fn game_type(&self) -> Option<u32> {
+        Some(40)
+    }
+
+    fn starting_pieces(&self) -> Vec<(Position, Piece)> {
+        starting_men(self, 4)
+    }
+
+    fn first_player(&self) -> Player {
+        Player::White
+    }
+
+    fn move_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        piece.directions()
+    }
+
+    fn capture_directions(&self, _piece: &Piece) -> Vec<(i32, i32)> {
+        DIAGONALS.iter().chain(&ORTHOGONALS).copied().collect()
+    }
+
+    fn flying_kings(&self) -> bool {
+        true
+    }
+
+    fn king_move_limit(&self) -> Option<u32> {
+        Some(3)
+    }
+
+    fn choose_captures(&self, board: &Board, captures: Vec<LegalMove>) -> Vec<LegalMove> {
+        let is_king = |position: &Position| {
+            board
+                .get(position.x as usize, position.y as usize)
+                .is_some_and(|piece| piece.is_king())
+        };
+
+        // Counting a king as two men less a little, the fewer kings the
+        // better when the totals are the same
+        let priority = |capture: &LegalMove| {
+            let kings = capture.captured.iter().filter(|position| is_king(position));
+            let kings = kings.count();
+            let men = capture.captured.len() - kings;
+
+            (men + 2 * kings, Reverse(kings), is_king(&capture.initial()))
+        };
+
+        let best = captures.iter().map(priority).max();
+
+        captures
+            .into_iter()
+            .filter(|capture| Some(priority(capture)) == best)
+            .collect()
+    }
+
+    fn crowning(&self) -> Crowning {
+        Crowning::ContinuesAsMan
+    }
+}
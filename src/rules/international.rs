@@ -0,0 +1,56 @@
+use crate::{
+    board::Board,
+    piece::Piece,
+    player::Player,
+    r#move::{LegalMove, Position},
+    rules::{most_captured, starting_men, Crowning, RuleSet, DIAGONALS},
+};
+
+/// International draughts, played on a 10x10 board with 20 men a side. Men
+/// capture backwards as well as forwards, kings fly and the capture taking
+/// the most pieces has to be played. A man only gets crowned if a capture
+/// ends on the far row, passing over it isn't enough.
+#[derive(Debug)]
+pub struct International;
+
+impl RuleSet for International {
+    fn name(&self) -> &str {
+        "international"
+    }
+
+    fn size(&self) -> (i32, i32) {
+        (10, 10)
+    }
+
+    fn game_type(&self) -> Option<u32> {
+        Some(20)
+    }
+
+    fn starting_pieces(&self) -> Vec<(Position, Piece)> {
+        starting_men(self, 4)
+    }
+
+    fn first_player(&self) -> Player {
+        Player::White
+    }
+
+    fn move_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        piece.directions()
+    }
+
+    fn capture_directions(&self, _piece: &Piece) -> Vec<(i32, i32)> {
+        DIAGONALS.to_vec()
+    }
+
+    fn flying_kings(&self) -> bool {
+        true
+    }
+
+    fn choose_captures(&self, _board: &Board, captures: Vec<LegalMove>) -> Vec<LegalMove> {
+        most_captured(captures)
+    }
+
+    fn crowning(&self) -> Crowning {
+        Crowning::ContinuesAsMan
+    }
+}
@@ -0,0 +1,77 @@
+use std::cmp::Reverse;
+
+use crate::{
+    board::Board,
+    piece::Piece,
+    player::Player,
+    r#move::{LegalMove, Position},
+    rules::{most_captured, starting_men, RuleSet},
+};
+
+/// Italian draughts (dama), played on an 8x8 board turned the other way
+/// round so the playable squares are the ones American checkers leaves
+/// empty. Men only move and capture forwards and can't capture kings, kings
+/// only move one square. When there is a choice of captures the one taking
+/// the most pieces has to be played, then one made with a king, then the
+/// one taking the most kings and finally the one reaching a king soonest.
+#[derive(Debug)]
+pub struct Italian;
+
+impl RuleSet for Italian {
+    fn name(&self) -> &str {
+        "italian"
+    }
+
+    fn is_playable(&self, position: Position) -> bool {
+        (position.x + position.y) % 2 == 0
+    }
+
+    fn game_type(&self) -> Option<u32> {
+        Some(22)
+    }
+
+    fn starting_pieces(&self) -> Vec<(Position, Piece)> {
+        starting_men(self, 3)
+    }
+
+    fn first_player(&self) -> Player {
+        Player::White
+    }
+
+    fn move_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        piece.directions()
+    }
+
+    fn can_capture(&self, piece: &Piece, target: &Piece) -> bool {
+        piece.is_king() || !target.is_king()
+    }
+
+    fn choose_captures(&self, board: &Board, captures: Vec<LegalMove>) -> Vec<LegalMove> {
+        let is_king = |position: &Position| {
+            board
+                .get(position.x as usize, position.y as usize)
+                .is_some_and(|piece| piece.is_king())
+        };
+
+        // Every rule after the number of pieces, best first
+        let priority = |capture: &LegalMove| {
+            let kings = capture.captured.iter().filter(|position| is_king(position));
+            let first_king = capture.captured.iter().position(is_king);
+
+            (
+                is_king(&capture.initial()),
+                kings.count(),
+                Reverse(first_king.unwrap_or(usize::MAX)),
+            )
+        };
+
+        let captures = most_captured(captures);
+
+        let best = captures.iter().map(priority).max();
+
+        captures
+            .into_iter()
+            .filter(|capture| Some(priority(capture)) == best)
+            .collect()
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{
+    board::Board,
+    piece::Piece,
+    player::Player,
+    r#move::{LegalMove, Position},
+    rules::{Crowning, RuleSet, Rules},
+};
+
+/// Any of the other rules played on a board of a different size, for
+/// trying out small boards like 6x6 or odd shapes like 10x8. The pieces
+/// start as `RuleSet::starting_pieces_on` lays them out, unless the board
+/// is the rules' own size.
+#[derive(Debug)]
+pub struct Resized {
+    rules: Rules,
+    size: (i32, i32),
+    name: String,
+}
+
+impl Resized {
+    pub fn new(rules: Rules, size: (i32, i32)) -> Self {
+        let name = format!("{}-{}x{}", rules.name(), size.0, size.1);
+
+        Self { rules, size, name }
+    }
+}
+
+// Anything that depends on the size of the board, numbering the squares
+// and crowning, is left to the defaults
+impl RuleSet for Resized {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    fn is_playable(&self, position: Position) -> bool {
+        self.rules.is_playable(position)
+    }
+
+    fn starting_pieces(&self) -> Vec<(Position, Piece)> {
+        if self.size == self.rules.size() {
+            return self.rules.starting_pieces();
+        }
+
+        self.rules.starting_pieces_on(self.size)
+    }
+
+    fn first_player(&self) -> Player {
+        self.rules.first_player()
+    }
+
+    fn pdn_first_player(&self) -> Player {
+        self.rules.pdn_first_player()
+    }
+
+    fn move_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        self.rules.move_directions(piece)
+    }
+
+    fn capture_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        self.rules.capture_directions(piece)
+    }
+
+    fn flying_kings(&self) -> bool {
+        self.rules.flying_kings()
+    }
+
+    fn can_capture(&self, piece: &Piece, target: &Piece) -> bool {
+        self.rules.can_capture(piece, target)
+    }
+
+    fn removes_captured_immediately(&self) -> bool {
+        self.rules.removes_captured_immediately()
+    }
+
+    fn king_move_limit(&self) -> Option<u32> {
+        self.rules.king_move_limit()
+    }
+
+    fn compulsory_capture(&self) -> bool {
+        self.rules.compulsory_capture()
+    }
+
+    fn choose_captures(&self, board: &Board, captures: Vec<LegalMove>) -> Vec<LegalMove> {
+        self.rules.choose_captures(board, captures)
+    }
+
+    fn crowning(&self) -> Crowning {
+        self.rules.crowning()
+    }
+
+    fn winner(&self, board: &Board) -> Option<Player> {
+        self.rules.winner(board)
+    }
+}
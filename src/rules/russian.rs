@@ -0,0 +1,48 @@
+use crate::{
+    piece::Piece,
+    player::Player,
+    r#move::Position,
+    rules::{starting_men, Crowning, RuleSet, DIAGONALS},
+};
+
+/// Russian draughts (shashki), played on the same board and from the same
+/// position as American checkers. Men capture backwards as well as forwards
+/// and kings fly, but any capture may be chosen, it doesn't have to take the
+/// most pieces. A man that reaches the far row part way through a capture is
+/// crowned there and carries on capturing as a king.
+#[derive(Debug)]
+pub struct Russian;
+
+impl RuleSet for Russian {
+    fn name(&self) -> &str {
+        "russian"
+    }
+
+    fn game_type(&self) -> Option<u32> {
+        Some(25)
+    }
+
+    fn starting_pieces(&self) -> Vec<(Position, Piece)> {
+        starting_men(self, 3)
+    }
+
+    fn first_player(&self) -> Player {
+        Player::White
+    }
+
+    fn move_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        piece.directions()
+    }
+
+    fn capture_directions(&self, _piece: &Piece) -> Vec<(i32, i32)> {
+        DIAGONALS.to_vec()
+    }
+
+    fn flying_kings(&self) -> bool {
+        true
+    }
+
+    fn crowning(&self) -> Crowning {
+        Crowning::ContinuesAsKing
+    }
+}
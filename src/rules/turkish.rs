@@ -0,0 +1,76 @@
+use crate::{
+    board::Board,
+    piece::Piece,
+    player::Player,
+    r#move::{LegalMove, Position},
+    rules::{most_captured, Crowning, RuleSet},
+};
+
+/// Turkish draughts (dama), played on every square of an 8x8 board with 16
+/// men a side starting on the second and third rows. Men move and capture
+/// one square forwards or sideways, kings fly along rows and columns. The
+/// capture taking the most pieces has to be played and every piece is taken
+/// off the board as soon as it is jumped.
+#[derive(Debug)]
+pub struct Turkish;
+
+impl RuleSet for Turkish {
+    fn name(&self) -> &str {
+        "turkish"
+    }
+
+    fn is_playable(&self, _position: Position) -> bool {
+        true
+    }
+
+    fn game_type(&self) -> Option<u32> {
+        Some(30)
+    }
+
+    fn starting_pieces(&self) -> Vec<(Position, Piece)> {
+        self.starting_pieces_on(self.size())
+    }
+
+    /// Men on every square of the second and third rows from each side,
+    /// whatever the size of the board
+    fn starting_pieces_on(&self, (width, height): (i32, i32)) -> Vec<(Position, Piece)> {
+        (0..width)
+            .flat_map(|x| {
+                vec![
+                    (Position { x, y: 1 }, Piece::man(Player::White)),
+                    (Position { x, y: 2 }, Piece::man(Player::White)),
+                    (Position { x, y: height - 3 }, Piece::man(Player::Red)),
+                    (Position { x, y: height - 2 }, Piece::man(Player::Red)),
+                ]
+            })
+            .collect()
+    }
+
+    fn first_player(&self) -> Player {
+        Player::White
+    }
+
+    fn move_directions(&self, piece: &Piece) -> Vec<(i32, i32)> {
+        if piece.is_king() {
+            vec![(1, 0), (-1, 0), (0, 1), (0, -1)]
+        } else {
+            vec![(1, 0), (-1, 0), (0, piece.player.forward())]
+        }
+    }
+
+    fn flying_kings(&self) -> bool {
+        true
+    }
+
+    fn removes_captured_immediately(&self) -> bool {
+        true
+    }
+
+    fn choose_captures(&self, _board: &Board, captures: Vec<LegalMove>) -> Vec<LegalMove> {
+        most_captured(captures)
+    }
+
+    fn crowning(&self) -> Crowning {
+        Crowning::ContinuesAsMan
+    }
+}
@@ -0,0 +1,330 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use crate::{
+    board::{Board, InvalidSquare},
+    parser::parse_position,
+    piece::Piece,
+    player::Player,
+    r#move::{Coordinates, Position},
+    rules::{self, Rules},
+};
+
+/// A position to start a game from, where every piece is and whose turn it is.
+///
+/// Written as `;` separated fields, for example
+/// `to-move red; white 1,0 K3,2; red 2,5`. Squares are `x,y` coordinates or
+/// algebraic names like `c3`, and a leading `K` marks a king. Any field can be left out, `to-move`
+/// defaults to white and a missing colour has no pieces.
+///
+/// A PDN FEN string such as `W:W21,22,K30:B1,2,3` is accepted as well, see
+/// `Setup::from_fen`.
+#[derive(Debug, Clone)]
+pub struct Setup {
+    pub board: Board,
+    pub to_move: Player,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SetupError {
+    UnknownField(String),
+    UnknownPlayer(String),
+    InvalidSquare(String),
+    DuplicateSquare(Position),
+    Board(InvalidSquare),
+    InvalidFen(String),
+}
+
+impl fmt::Display for SetupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownField(field) => write!(f, "unknown field `{}`", field),
+            Self::UnknownPlayer(player) => write!(f, "unknown player `{}`", player),
+            Self::InvalidSquare(square) => write!(f, "`{}` is not a square", square),
+            Self::DuplicateSquare(pos) => write!(f, "{} holds more than one piece", pos),
+            Self::Board(err) => write!(f, "{}", err),
+            Self::InvalidFen(fen) => write!(f, "`{}` is not a FEN position", fen),
+        }
+    }
+}
+
+impl Error for SetupError {}
+
+impl Default for Setup {
+    fn default() -> Self {
+        Self::new(rules::american())
+    }
+}
+
+impl Setup {
+    /// The starting position of the given rules with their first player to
+    /// move
+    pub fn new(rules: Rules) -> Self {
+        Self {
+            to_move: rules.first_player(),
+            board: Board::with_rules(rules),
+        }
+    }
+
+    /// Parses a PDN FEN string like `W:W21,22,K30:B1,2,3`, squares use the
+    /// standard numbering from `RuleSet::square_position`. White is white and
+    /// black is red, ranges such as `B1-12` are allowed.
+    pub fn from_fen(src: &str) -> Result<Self, SetupError> {
+        Self::from_fen_with_rules(src, rules::american())
+    }
+
+    /// Same as `from_fen` on the board of `rules`, squares are numbered as in
+    /// `RuleSet::square_position`
+    pub fn from_fen_with_rules(src: &str, rules: Rules) -> Result<Self, SetupError> {
+        let fen = src.trim().trim_matches('"').trim_end_matches('.');
+
+        let invalid = || SetupError::InvalidFen(src.to_string());
+
+        let mut fields = fen.split(':');
+
+        let to_move = match fields.next().map(str::trim) {
+            Some("W") => Player::White,
+            Some("B") => Player::Red,
+            _ => return Err(invalid()),
+        };
+
+        let mut pieces: Vec<(Position, Piece)> = vec![];
+
+        for field in fields.map(str::trim) {
+            let (player, squares) = match field.split_at_checked(1) {
+                Some(("W", squares)) => (Player::White, squares),
+                Some(("B", squares)) => (Player::Red, squares),
+                _ => return Err(invalid()),
+            };
+
+            for word in squares.split(',').map(str::trim).filter(|w| !w.is_empty()) {
+                let (numbers, king) = match word.strip_prefix('K') {
+                    Some(numbers) => (numbers, true),
+                    None => (word, false),
+                };
+
+                let parse = |n: &str| {
+                    n.parse::<u32>()
+                        .map_err(|_| SetupError::InvalidSquare(word.to_string()))
+                };
+
+                let (first, last) = match numbers.split_once('-') {
+                    Some((first, last)) => (parse(first)?, parse(last)?),
+                    None => (parse(numbers)?, parse(numbers)?),
+                };
+
+                for square in first..=last {
+                    let position = rules
+                        .square_position(square)
+                        .ok_or_else(|| SetupError::InvalidSquare(word.to_string()))?;
+
+                    if pieces.iter().any(|(other, _)| *other == position) {
+                        return Err(SetupError::DuplicateSquare(position));
+                    }
+
+                    let piece = if king {
+                        Piece::king(player)
+                    } else {
+                        Piece::man(player)
+                    };
+
+                    pieces.push((position, piece));
+                }
+            }
+        }
+
+        let board = Board::from_pieces_with_rules(rules, pieces).map_err(SetupError::Board)?;
+
+        Ok(Self { board, to_move })
+    }
+
+    /// Parses a setup in either format on the board of `rules`, the `FromStr`
+    /// implementation uses American checkers
+    pub fn parse_with_rules(src: &str, rules: Rules) -> Result<Self, SetupError> {
+        Self::parse_with_coordinates(src, rules, Coordinates::default())
+    }
+
+    /// Same as `parse_with_rules` with `x,y` squares written in the given
+    /// coordinates
+    pub fn parse_with_coordinates(
+        src: &str,
+        rules: Rules,
+        coordinates: Coordinates,
+    ) -> Result<Self, SetupError> {
+        let trimmed = src.trim();
+
+        if trimmed.starts_with("W:") || trimmed.starts_with("B:") {
+            return Self::from_fen_with_rules(trimmed, rules);
+        }
+
+        let mut to_move = Player::White;
+        let mut pieces: Vec<(Position, Piece)> = vec![];
+
+        for field in src
+            .split(';')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+        {
+            let mut words = field.split_whitespace();
+
+            match words.next() {
+                Some("to-move") => {
+                    let player = words.next().unwrap_or_default();
+
+                    to_move = parse_player(player)?;
+                }
+                Some(name @ ("white" | "red")) => {
+                    let player = parse_player(name)?;
+
+                    for word in words {
+                        let (position, piece) =
+                            parse_piece(word, player, rules.size(), coordinates)?;
+
+                        if pieces.iter().any(|(other, _)| *other == position) {
+                            return Err(SetupError::DuplicateSquare(position));
+                        }
+
+                        pieces.push((position, piece));
+                    }
+                }
+                _ => return Err(SetupError::UnknownField(field.to_string())),
+            }
+        }
+
+        let board = Board::from_pieces_with_rules(rules, pieces).map_err(SetupError::Board)?;
+
+        Ok(Self { board, to_move })
+    }
+
+    /// Writes the position as a PDN FEN string, squares in ascending order
+    pub fn to_fen(&self) -> String {
+        let turn = match self.to_move {
+            Player::White => "W",
+            Player::Red => "B",
+        };
+
+        let rules = self.board.rules();
+
+        let squares = |player: Player| {
+            let mut pieces: Vec<(u32, Piece)> = self
+                .board
+                .occupied()
+                .filter(|(_, piece)| piece.player == player)
+                .filter_map(|(position, piece)| {
+                    rules.square_number(position).map(|square| (square, piece))
+                })
+                .collect();
+
+            pieces.sort_by_key(|(square, _)| *square);
+
+            pieces
+                .iter()
+                .map(|(square, piece)| {
+                    let king = if piece.is_king() { "K" } else { "" };
+
+                    format!("{}{}", king, square)
+                })
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+
+        format!(
+            "{}:W{}:B{}",
+            turn,
+            squares(Player::White),
+            squares(Player::Red)
+        )
+    }
+}
+
+impl FromStr for Setup {
+    type Err = SetupError;
+
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_rules(src, rules::american())
+    }
+}
+
+impl fmt::Display for Setup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "to-move {}", self.to_move)?;
+
+        for player in [Player::White, Player::Red] {
+            write!(f, "; {}", player)?;
+
+            for (position, piece) in self.board.occupied() {
+                if piece.player != player {
+                    continue;
+                }
+
+                let king = if piece.is_king() { "K" } else { "" };
+
+                write!(f, " {}{}", king, position)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_player(src: &str) -> Result<Player, SetupError> {
+    match src {
+        "white" => Ok(Player::White),
+        "red" => Ok(Player::Red),
+        _ => Err(SetupError::UnknownPlayer(src.to_string())),
+    }
+}
+
+/// A square with an optional `K` in front for a king
+pub(crate) fn parse_piece(
+    src: &str,
+    player: Player,
+    size: (i32, i32),
+    coordinates: Coordinates,
+) -> Result<(Position, Piece), SetupError> {
+    let (square, piece) = match src.strip_prefix('K') {
+        Some(square) => (square, Piece::king(player)),
+        None => (src, Piece::man(player)),
+    };
+
+    let position = parse_position(square, size, coordinates)
+        .ok_or_else(|| SetupError::InvalidSquare(src.to_string()))?;
+
+    Ok((position, piece))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let setup: Setup = "to-move red; white 1,0 K3,2; red 2,5".parse().unwrap();
+
+        assert_eq!(setup.to_move, Player::Red);
+        assert_eq!(setup.board.get(3, 2), Some(Piece::king(Player::White)));
+        assert_eq!(setup.to_string(), "to-move red; white 1,0 K3,2; red 2,5");
+
+        assert_eq!(
+            "white 1,0; red 1,0".parse::<Setup>().unwrap_err(),
+            SetupError::DuplicateSquare(Position { x: 1, y: 0 })
+        );
+    }
+
+    #[test]
+    fn fen() {
+        let setup = Setup::from_fen("W:W21,22,K30:B1,2,3").unwrap();
+
+        assert_eq!(setup.to_move, Player::White);
+        assert_eq!(setup.board.get(5, 0), Some(Piece::king(Player::White)));
+        assert_eq!(setup.board.get(6, 7), Some(Piece::man(Player::Red)));
+        assert_eq!(setup.to_fen(), "W:W21,22,K30:B1,2,3");
+
+        let start = Setup::from_fen("B:W21-32:B1-12").unwrap();
+
+        assert_eq!(start.to_move, Player::Red);
+        assert_eq!(
+            start.board.occupied().collect::<Vec<_>>(),
+            Board::new().occupied().collect::<Vec<_>>()
+        );
+    }
+}